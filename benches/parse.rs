@@ -0,0 +1,167 @@
+//! Run with `cargo bench`. Reports the time and the number of heap
+//! allocations per iteration, and the heap memory some parsed values keep.
+
+use json_parser::{is_valid, parse, parse_lazy, parse_shared, tokenize, Parser};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+const SAMPLE: &str = include_str!("sample.json");
+
+/// Counts every allocation and reallocation made through the global allocator,
+/// and the bytes currently allocated
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static LIVE_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        LIVE_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        LIVE_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    let allocations_before = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed();
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - allocations_before;
+
+    println!(
+        "{name:<32} {:>12.2?}/iter {:>8} allocs/iter",
+        elapsed / iterations,
+        allocations / iterations as usize
+    );
+}
+
+/// Reports the heap memory still held by the value `f` returns
+fn retained<T>(name: &str, f: impl FnOnce() -> T) {
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    let value = black_box(f());
+    let bytes = LIVE_BYTES.load(Ordering::Relaxed) - before;
+    drop(value);
+
+    println!("{name:<32} {:>10} KiB retained", bytes / 1024);
+}
+
+/// An array of long strings with a few escapes each
+fn string_heavy_document() -> String {
+    let items: Vec<String> = (0..1_000)
+        .map(|i| format!(r#""item {i}: lorem ipsum dolor sit amet \"quoted\" \u00e9\n""#))
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+/// An array of records that repeat a handful of long labels
+fn repeated_strings_document() -> String {
+    const LABELS: [&str; 3] = [
+        "排名前10%，起点范围内",
+        "排名前10-40%，起点范围内",
+        "排名前40-100%",
+    ];
+    let records: Vec<String> = (0..10_000)
+        .map(|i| {
+            format!(
+                r#"{{"rank": {i}, "label": "{}"}}"#,
+                LABELS[i % LABELS.len()]
+            )
+        })
+        .collect();
+    format!("[{}]", records.join(","))
+}
+
+/// An object whose keys all need unescaping
+fn escaped_keys_document() -> String {
+    let members: Vec<String> = (0..1_000)
+        .map(|i| format!(r#""key\t{i}\u00e9": {i}"#))
+        .collect();
+    format!("{{{}}}", members.join(","))
+}
+
+fn main() {
+    bench("tokenize sample", 10_000, || {
+        black_box(tokenize(black_box(SAMPLE)).unwrap());
+    });
+    bench("parse sample", 10_000, || {
+        black_box(parse(black_box(SAMPLE)).unwrap());
+    });
+
+    let large = format!("[{}]", vec![SAMPLE; 1_000].join(","));
+    bench("tokenize large", 10, || {
+        black_box(tokenize(black_box(&large)).unwrap());
+    });
+
+    let strings = string_heavy_document();
+    bench("parse string-heavy", 1_000, || {
+        black_box(parse(black_box(&strings)).unwrap());
+    });
+    bench("is_valid string-heavy", 1_000, || {
+        assert!(is_valid(black_box(&strings)));
+    });
+
+    let small_documents: Vec<String> = (0..10_000)
+        .map(|i| format!(r#"{{"id": {i}, "tags": ["a", "b"], "ok": true}}"#))
+        .collect();
+    bench("parse 10k small documents", 10, || {
+        for document in &small_documents {
+            black_box(parse(black_box(document)).unwrap());
+        }
+    });
+    let mut parser = Parser::new();
+    bench("parse_reuse 10k small documents", 10, || {
+        for document in &small_documents {
+            black_box(parser.parse_reuse(black_box(document)).unwrap());
+        }
+    });
+
+    let escaped_documents: Vec<String> = (0..10_000)
+        .map(|i| format!(r#"{{"id": {i}, "name": "line\n{i}", "note": "\"quoted\""}}"#))
+        .collect();
+    bench("parse 10k escaped documents", 10, || {
+        for document in &escaped_documents {
+            black_box(parse(black_box(document)).unwrap());
+        }
+    });
+    bench("parse_reuse 10k escaped documents", 10, || {
+        for document in &escaped_documents {
+            black_box(parser.parse_reuse(black_box(document)).unwrap());
+        }
+    });
+
+    let escaped_keys = escaped_keys_document();
+    bench("parse escaped keys, read one", 1_000, || {
+        let value = parse(black_box(&escaped_keys)).unwrap();
+        black_box(value.pointer("/key\t500\u{e9}").unwrap());
+    });
+    bench("parse_lazy escaped keys, read one", 1_000, || {
+        let value = parse_lazy(black_box(&escaped_keys)).unwrap();
+        black_box(value.get("key\t500\u{e9}").unwrap());
+    });
+
+    let repeated = repeated_strings_document();
+    retained("parse repeated strings", || parse(&repeated).unwrap());
+    retained("parse_shared repeated strings", || {
+        parse_shared(&repeated).unwrap()
+    });
+}
@@ -0,0 +1,89 @@
+use crate::value::Value;
+
+/// RFC 4648 标准字母表
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+impl Value {
+    /// Builds a `String` value holding `bytes` in standard, padded base64,
+    /// the usual way of embedding binary data in JSON
+    pub fn encode_base64(bytes: &[u8]) -> Value {
+        let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+                group | u32::from(byte) << (16 - 8 * i)
+            });
+            // 每3个字节输出 chunk.len() + 1 个字符，不足4个用 `=` 补齐
+            for i in 0..4 {
+                if i <= chunk.len() {
+                    output.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+                } else {
+                    output.push('=');
+                }
+            }
+        }
+        Value::String(output)
+    }
+
+    /// Decodes a `String` value written in standard, padded base64
+    ///
+    /// Returns `None` for other values and for strings that are not valid
+    /// base64, including ones with whitespace or missing padding.
+    pub fn decode_base64(&self) -> Option<Vec<u8>> {
+        let Value::String(string) = self else {
+            return None;
+        };
+        let input = string.as_bytes();
+        if input.len() % 4 != 0 {
+            return None;
+        }
+
+        let mut output = Vec::with_capacity(input.len() / 4 * 3);
+        for (n, chunk) in input.chunks(4).enumerate() {
+            let is_last = n == input.len() / 4 - 1;
+            let padding = chunk.iter().rev().take_while(|&&byte| byte == b'=').count();
+            if padding > 2 || (padding > 0 && !is_last) {
+                return None;
+            }
+            let mut group = 0u32;
+            for &byte in &chunk[..4 - padding] {
+                let digit = ALPHABET.iter().position(|&c| c == byte)?;
+                group = group << 6 | digit as u32;
+            }
+            group <<= 6 * padding;
+            let bytes = group.to_be_bytes();
+            output.extend_from_slice(&bytes[1..4 - padding]);
+        }
+        Some(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::value::Value;
+
+    #[test]
+    fn encodes_with_padding() {
+        assert_eq!(Value::encode_base64(b""), "");
+        assert_eq!(Value::encode_base64(b"f"), "Zg==");
+        assert_eq!(Value::encode_base64(b"fo"), "Zm8=");
+        assert_eq!(Value::encode_base64(b"foo"), "Zm9v");
+        assert_eq!(Value::encode_base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn round_trips_bytes() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for len in [0, 1, 2, 3, 255, 256] {
+            let value = Value::encode_base64(&bytes[..len]);
+            assert_eq!(value.decode_base64().as_deref(), Some(&bytes[..len]));
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_base64() {
+        for input in ["Zg", "Zg=a", "Z===", "Zg==Zg==", "Zm9v YmFy", "Zm9*"] {
+            assert_eq!(Value::String(input.into()).decode_base64(), None, "{input}");
+        }
+        assert_eq!(Value::Null.decode_base64(), None);
+    }
+}
@@ -0,0 +1,388 @@
+use crate::number::Number;
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// One of the possible errors that could occur while converting a `Value`
+/// into a Rust type
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ConversionError {
+    /// The value was not of the kind the target type is built from
+    UnexpectedType {
+        /// Name of the JSON type that was expected, e.g. `"number"`
+        expected: &'static str,
+    },
+    /// The array element at this index failed to convert
+    AtIndex(usize, Box<ConversionError>),
+    /// The object value under this key failed to convert
+    AtKey(String, Box<ConversionError>),
+}
+
+/// Scalar type a value can be coerced to by [`Value::retype`]
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum JsonType {
+    /// A number, from a numeric string such as `"70"` or from a boolean as
+    /// `1`/`0`
+    Number,
+    /// A string, from the JSON text of a number or boolean
+    String,
+    /// A boolean, from anything [`Value::as_bool_lenient`] accepts
+    Boolean,
+}
+
+impl JsonType {
+    fn name(self) -> &'static str {
+        match self {
+            JsonType::Number => "number",
+            JsonType::String => "string",
+            JsonType::Boolean => "boolean",
+        }
+    }
+}
+
+/// Types that can be built from a borrowed `Value`
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, ConversionError>;
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Number(number) => Ok(number.as_f64()),
+            _ => Err(ConversionError::UnexpectedType { expected: "number" }),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Boolean(boolean) => Ok(*boolean),
+            _ => Err(ConversionError::UnexpectedType {
+                expected: "boolean",
+            }),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::String(string) => Ok(string.clone()),
+            _ => Err(ConversionError::UnexpectedType { expected: "string" }),
+        }
+    }
+}
+
+/// Converts every element of an `Array`, reporting the first element that fails
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Array(array) => array
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    T::from_value(item).map_err(|e| ConversionError::AtIndex(i, Box::new(e)))
+                })
+                .collect(),
+            _ => Err(ConversionError::UnexpectedType { expected: "array" }),
+        }
+    }
+}
+
+/// Converts every value of an `Object`, reporting a key whose value fails
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Object(map) => map
+                .iter()
+                .map(|(key, item)| {
+                    T::from_value(item)
+                        .map(|converted| (key.clone(), converted))
+                        .map_err(|e| ConversionError::AtKey(key.clone(), Box::new(e)))
+                })
+                .collect(),
+            _ => Err(ConversionError::UnexpectedType { expected: "object" }),
+        }
+    }
+}
+
+/// Maps `Null` to `None` and converts anything else as `T`
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, ConversionError> {
+        match value {
+            Value::Null => Ok(None),
+            other => T::from_value(other).map(Some),
+        }
+    }
+}
+
+impl Value {
+    /// Converts every element of an array to `T`, e.g.
+    /// `value.as_array_of::<f64>()`, reporting the index of the first element
+    /// that fails
+    pub fn as_array_of<T: FromValue>(&self) -> Result<Vec<T>, ConversionError> {
+        Vec::<T>::from_value(self)
+    }
+
+    /// Converts every value of an object to `T`, e.g.
+    /// `value.as_map_of::<f64>()`, reporting the key of a value that fails
+    ///
+    /// If several values fail, which key is reported is unspecified, as
+    /// objects are unordered.
+    pub fn as_map_of<T: FromValue>(&self) -> Result<HashMap<String, T>, ConversionError> {
+        HashMap::<String, T>::from_value(self)
+    }
+
+    /// Normalizes loosely typed input by coercing the value at each JSON
+    /// Pointer of `schema` to its target type, e.g.
+    /// `[("/total_income", JsonType::Number)]` turns `"70"` into `70`
+    ///
+    /// Pointers that address nothing are skipped. Values that cannot be
+    /// coerced are left unchanged and reported together with their pointer,
+    /// in schema order.
+    pub fn retype(
+        &mut self,
+        schema: &[(&str, JsonType)],
+    ) -> Result<(), Vec<(String, ConversionError)>> {
+        let mut errors = Vec::new();
+        for &(pointer, target) in schema {
+            let Some(value) = self.pointer_mut(pointer) else {
+                continue;
+            };
+            match value.coerced(target) {
+                Some(coerced) => *value = coerced,
+                None => errors.push((
+                    pointer.to_owned(),
+                    ConversionError::UnexpectedType {
+                        expected: target.name(),
+                    },
+                )),
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// 转换成 `target` 类型的值，无法转换时返回 `None`
+    fn coerced(&self, target: JsonType) -> Option<Value> {
+        match (target, self) {
+            (JsonType::Number, Value::Number(_))
+            | (JsonType::String, Value::String(_))
+            | (JsonType::Boolean, Value::Boolean(_)) => Some(self.clone()),
+            (JsonType::Number, Value::String(string)) => match crate::parse(string.trim()) {
+                Ok(number @ Value::Number(_)) => Some(number),
+                _ => None,
+            },
+            (JsonType::Number, Value::Boolean(boolean)) => {
+                Some(Value::Number(Number::I64(i64::from(*boolean))))
+            }
+            (JsonType::String, Value::Number(_) | Value::Boolean(_)) => {
+                Some(Value::String(self.to_string()))
+            }
+            (JsonType::Boolean, _) => self.as_bool_lenient().map(Value::Boolean),
+            _ => None,
+        }
+    }
+
+    /// Reads a boolean the way loosely typed config often spells it: a real
+    /// boolean, the numbers `1` and `0`, or (ignoring case) the strings
+    /// `"true"`/`"false"`, `"yes"`/`"no"` and `"1"`/`"0"`
+    pub fn as_bool_lenient(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(boolean) => Some(*boolean),
+            Value::Number(number) if number.as_f64() == 1.0 => Some(true),
+            Value::Number(number) if number.as_f64() == 0.0 => Some(false),
+            Value::String(string) => match string.to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Some(true),
+                "false" | "no" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ConversionError, FromValue, JsonType};
+    use crate::number::Number;
+    use crate::value::Value;
+    use std::collections::HashMap;
+
+    #[test]
+    fn converts_scalars() {
+        assert_eq!(f64::from_value(&Value::Number(Number::F64(1.5))), Ok(1.5));
+        assert_eq!(bool::from_value(&Value::Boolean(true)), Ok(true));
+        assert_eq!(
+            String::from_value(&Value::String("hi".into())),
+            Ok(String::from("hi"))
+        );
+    }
+
+    #[test]
+    fn rejects_mismatched_type() {
+        assert_eq!(
+            f64::from_value(&Value::Null),
+            Err(ConversionError::UnexpectedType { expected: "number" })
+        );
+    }
+
+    #[test]
+    fn option_converts_non_null() {
+        let value = Value::String("hi".into());
+        assert_eq!(
+            Option::<String>::from_value(&value),
+            Ok(Some(String::from("hi")))
+        );
+    }
+
+    #[test]
+    fn vec_reports_failing_index() {
+        let value = Value::Array(vec![Value::Number(Number::F64(1.0)), Value::Boolean(false)]);
+        assert_eq!(
+            Vec::<f64>::from_value(&value),
+            Err(ConversionError::AtIndex(
+                1,
+                Box::new(ConversionError::UnexpectedType { expected: "number" })
+            ))
+        );
+    }
+
+    #[test]
+    fn as_array_of_mixed_array_reports_index() {
+        let value = Value::Array(vec![
+            Value::String("a".into()),
+            Value::String("b".into()),
+            Value::Number(Number::F64(3.0)),
+        ]);
+        assert_eq!(
+            value.as_array_of::<String>(),
+            Err(ConversionError::AtIndex(
+                2,
+                Box::new(ConversionError::UnexpectedType { expected: "string" })
+            ))
+        );
+    }
+
+    #[test]
+    fn as_array_of_non_array() {
+        assert_eq!(
+            Value::Null.as_array_of::<f64>(),
+            Err(ConversionError::UnexpectedType { expected: "array" })
+        );
+    }
+
+    #[test]
+    fn lenient_bool_from_boolean_and_numbers() {
+        assert_eq!(Value::Boolean(false).as_bool_lenient(), Some(false));
+        assert_eq!(
+            Value::Number(Number::F64(1.0)).as_bool_lenient(),
+            Some(true)
+        );
+        assert_eq!(
+            Value::Number(Number::F64(0.0)).as_bool_lenient(),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn lenient_bool_from_strings() {
+        for (input, expected) in [
+            ("true", true),
+            ("FALSE", false),
+            ("Yes", true),
+            ("no", false),
+            ("1", true),
+            ("0", false),
+        ] {
+            let value = Value::String(input.into());
+            assert_eq!(value.as_bool_lenient(), Some(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn lenient_bool_rejects_other_values() {
+        assert_eq!(Value::Number(Number::F64(2.0)).as_bool_lenient(), None);
+        assert_eq!(Value::String("maybe".into()).as_bool_lenient(), None);
+        assert_eq!(Value::Null.as_bool_lenient(), None);
+    }
+
+    #[test]
+    fn map_of_numbers() {
+        let value: Value = [
+            ("a".to_string(), Value::Number(Number::F64(1.0))),
+            ("b".to_string(), Value::Number(Number::F64(2.0))),
+        ]
+        .into_iter()
+        .collect();
+        let expected = HashMap::from([("a".to_string(), 1.0), ("b".to_string(), 2.0)]);
+        assert_eq!(value.as_map_of::<f64>(), Ok(expected));
+    }
+
+    #[test]
+    fn map_reports_failing_key() {
+        let value: Value = [
+            ("a".to_string(), Value::Number(Number::F64(1.0))),
+            ("b".to_string(), Value::Null),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            value.as_map_of::<f64>(),
+            Err(ConversionError::AtKey(
+                "b".into(),
+                Box::new(ConversionError::UnexpectedType { expected: "number" })
+            ))
+        );
+        assert_eq!(
+            Value::Array(vec![]).as_map_of::<f64>(),
+            Err(ConversionError::UnexpectedType { expected: "object" })
+        );
+    }
+
+    #[test]
+    fn retypes_fields_by_schema() {
+        let mut value = crate::parse(
+            r#"{"my_rank_info": {"total_income": "70", "rank": 3, "cur_stage": "yes"}}"#,
+        )
+        .unwrap();
+        let schema = [
+            ("/my_rank_info/total_income", JsonType::Number),
+            ("/my_rank_info/rank", JsonType::String),
+            ("/my_rank_info/cur_stage", JsonType::Boolean),
+            ("/my_rank_info/missing", JsonType::Number),
+        ];
+        assert_eq!(value.retype(&schema), Ok(()));
+        assert_eq!(value["my_rank_info"]["total_income"], 70.0);
+        assert_eq!(value["my_rank_info"]["rank"], "3");
+        assert_eq!(value["my_rank_info"]["cur_stage"], true);
+    }
+
+    #[test]
+    fn retype_reports_failing_paths() {
+        let mut value = crate::parse(r#"{"a": "seventy", "b": [1], "c": "1"}"#).unwrap();
+        let schema = [
+            ("/a", JsonType::Number),
+            ("/b", JsonType::String),
+            ("/c", JsonType::Number),
+        ];
+        assert_eq!(
+            value.retype(&schema),
+            Err(vec![
+                (
+                    "/a".into(),
+                    ConversionError::UnexpectedType { expected: "number" }
+                ),
+                (
+                    "/b".into(),
+                    ConversionError::UnexpectedType { expected: "string" }
+                ),
+            ])
+        );
+        assert_eq!(value["a"], "seventy");
+        assert_eq!(value["c"], 1.0);
+    }
+}
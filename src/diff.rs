@@ -0,0 +1,221 @@
+use crate::pointer::escape_token;
+use crate::value::Value;
+use std::collections::BTreeSet;
+
+/// Top-level keys that differ between two objects, see [`Value::diff_keys`]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct KeyDiff {
+    /// Keys present only in the first object
+    pub removed: BTreeSet<String>,
+    /// Keys present only in the second object
+    pub added: BTreeSet<String>,
+    /// Keys present in both objects with different values
+    pub changed: BTreeSet<String>,
+}
+
+impl Value {
+    /// Compares the top-level keys of two objects, e.g. an old and a new
+    /// config; returns `None` unless both values are objects
+    pub fn diff_keys(&self, other: &Value) -> Option<KeyDiff> {
+        let (Value::Object(left), Value::Object(right)) = (self, other) else {
+            return None;
+        };
+
+        let mut diff = KeyDiff::default();
+        for (key, value) in left {
+            match right.get(key) {
+                None => {
+                    diff.removed.insert(key.clone());
+                }
+                Some(other_value) if other_value != value => {
+                    diff.changed.insert(key.clone());
+                }
+                Some(_) => {}
+            }
+        }
+        diff.added = right
+            .keys()
+            .filter(|key| !left.contains_key(*key))
+            .cloned()
+            .collect();
+        Some(diff)
+    }
+
+    /// Returns the JSON Pointer to the first place where the two trees
+    /// differ, or `None` if they are equal
+    ///
+    /// Array elements are compared in order and object members in key order,
+    /// so the reported path is deterministic. A member or element present on
+    /// only one side is reported at its own path.
+    pub fn first_difference(&self, other: &Value) -> Option<String> {
+        let mut stack = vec![(String::new(), Some(self), Some(other))];
+
+        while let Some((path, left, right)) = stack.pop() {
+            let (Some(left), Some(right)) = (left, right) else {
+                return Some(path);
+            };
+            match (left, right) {
+                (Value::Array(left), Value::Array(right)) => {
+                    let len = left.len().max(right.len());
+                    // 逆序入栈，保证先比较前面的元素
+                    for i in (0..len).rev() {
+                        stack.push((format!("{path}/{i}"), left.get(i), right.get(i)));
+                    }
+                }
+                (Value::Object(left), Value::Object(right)) => {
+                    let keys: BTreeSet<_> = left.keys().chain(right.keys()).collect();
+                    for key in keys.into_iter().rev() {
+                        stack.push((
+                            format!("{path}/{}", escape_token(key)),
+                            left.get(key),
+                            right.get(key),
+                        ));
+                    }
+                }
+                (left, right) if left == right => {}
+                _ => return Some(path),
+            }
+        }
+        None
+    }
+
+    /// Returns an object of the entries of `self` whose keys also appear in
+    /// `other`, keeping the values of `self`; `None` unless both are objects
+    pub fn object_intersection(&self, other: &Value) -> Option<Value> {
+        self.filter_keys(other, true)
+    }
+
+    /// Returns an object of the entries of `self` whose keys do not appear in
+    /// `other`; `None` unless both are objects
+    pub fn object_difference(&self, other: &Value) -> Option<Value> {
+        self.filter_keys(other, false)
+    }
+
+    /// 保留键是否出现在 `other` 中与 `in_other` 一致的项
+    fn filter_keys(&self, other: &Value, in_other: bool) -> Option<Value> {
+        let (Value::Object(left), Value::Object(right)) = (self, other) else {
+            return None;
+        };
+        Some(
+            left.iter()
+                .filter(|(key, _)| right.contains_key(*key) == in_other)
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KeyDiff;
+    use crate::number::Number;
+    use crate::value::{object, Value};
+
+    #[test]
+    fn equal_trees_have_no_difference() {
+        let left = object(vec![("a", Value::Array(vec![Value::Null]))]);
+        let right = object(vec![("a", Value::Array(vec![Value::Null]))]);
+        assert_eq!(left.first_difference(&right), None);
+    }
+
+    #[test]
+    fn reports_first_differing_path() {
+        let left = object(vec![
+            ("a", Value::Number(Number::F64(1.0))),
+            (
+                "b",
+                object(vec![(
+                    "list",
+                    Value::Array(vec![Value::Null, Value::Boolean(true)]),
+                )]),
+            ),
+            ("c", Value::Number(Number::F64(1.0))),
+        ]);
+        let right = object(vec![
+            ("a", Value::Number(Number::F64(1.0))),
+            (
+                "b",
+                object(vec![(
+                    "list",
+                    Value::Array(vec![Value::Null, Value::Boolean(false)]),
+                )]),
+            ),
+            ("c", Value::Number(Number::F64(2.0))),
+        ]);
+        assert_eq!(left.first_difference(&right), Some("/b/list/1".into()));
+    }
+
+    #[test]
+    fn reports_missing_members_and_elements() {
+        let left = object(vec![("a", Value::Null)]);
+        let right = object(vec![("a", Value::Null), ("a/b", Value::Null)]);
+        assert_eq!(left.first_difference(&right), Some("/a~1b".into()));
+
+        let left = Value::Array(vec![Value::Null]);
+        let right = Value::Array(vec![]);
+        assert_eq!(left.first_difference(&right), Some("/0".into()));
+    }
+
+    #[test]
+    fn reports_root_type_mismatch() {
+        assert_eq!(
+            Value::Null.first_difference(&Value::Array(vec![])),
+            Some("".into())
+        );
+    }
+
+    #[test]
+    fn diff_keys_of_two_objects() {
+        let old = object(vec![
+            ("kept", Value::Number(Number::F64(1.0))),
+            ("changed", Value::Number(Number::F64(1.0))),
+            ("removed", Value::Null),
+        ]);
+        let new = object(vec![
+            ("kept", Value::Number(Number::F64(1.0))),
+            ("changed", Value::Number(Number::F64(2.0))),
+            ("added", Value::Null),
+        ]);
+
+        let expected = KeyDiff {
+            removed: ["removed".to_string()].into(),
+            added: ["added".to_string()].into(),
+            changed: ["changed".to_string()].into(),
+        };
+        assert_eq!(old.diff_keys(&new), Some(expected));
+    }
+
+    #[test]
+    fn diff_keys_requires_objects() {
+        let value = object(vec![]);
+        assert_eq!(value.diff_keys(&Value::Null), None);
+        assert_eq!(Value::Array(vec![]).diff_keys(&value), None);
+    }
+
+    #[test]
+    fn intersects_and_subtracts_objects() {
+        let base = object(vec![
+            ("host", Value::String("a".into())),
+            ("port", Value::Number(Number::F64(80.0))),
+            ("debug", Value::Boolean(true)),
+        ]);
+        let local = object(vec![
+            ("port", Value::Number(Number::F64(8080.0))),
+            ("user", Value::Null),
+        ]);
+
+        assert_eq!(
+            base.object_intersection(&local),
+            Some(object(vec![("port", Value::Number(Number::F64(80.0)))]))
+        );
+        assert_eq!(
+            base.object_difference(&local),
+            Some(object(vec![
+                ("host", Value::String("a".into())),
+                ("debug", Value::Boolean(true)),
+            ]))
+        );
+        assert_eq!(base.object_intersection(&Value::Null), None);
+        assert_eq!(Value::Null.object_difference(&base), None);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::options::ParseOptions;
+use crate::value::Value;
+use crate::{parse_with_options, ParseError};
+use std::task::Poll;
+
+/// Parses JSON values out of a byte stream that arrives in arbitrary chunks
+///
+/// Each fed chunk is scanned only once to track string and nesting state, so
+/// the buffered input is handed to [`crate::parse_with_options`] exactly once,
+/// when a complete
+/// value is available.
+#[derive(Debug, Default)]
+pub struct IncrementalParser {
+    options: ParseOptions,
+    buffer: Vec<u8>,
+    /// 已经扫描过的字节数
+    scanned: usize,
+    depth: usize,
+    /// 当前所在字符串的引号
+    quote: Option<u8>,
+    is_escaping: bool,
+    /// 当前值是否已经出现了非空白字符
+    started: bool,
+}
+
+impl IncrementalParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses every value with `options`
+    ///
+    /// Values are still split at brackets and quotes only, so a comment
+    /// allowed by `allow_comments` must not contain either.
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Appends `bytes` to the buffer and returns the next value once it is
+    /// complete
+    ///
+    /// Bytes following a completed value stay buffered; feed an empty slice
+    /// to parse further values that are already buffered. A top-level number
+    /// or literal is only known to be complete once whitespace or a
+    /// structural character such as `[` follows it, or when
+    /// [`IncrementalParser::finish`] is called.
+    pub fn feed(&mut self, bytes: &[u8]) -> Poll<Result<Value, ParseError>> {
+        self.buffer.extend_from_slice(bytes);
+
+        while self.scanned < self.buffer.len() {
+            let byte = self.buffer[self.scanned];
+            self.scanned += 1;
+
+            if let Some(quote) = self.quote {
+                match byte {
+                    _ if self.is_escaping => self.is_escaping = false,
+                    b'\\' => self.is_escaping = true,
+                    _ if byte == quote => {
+                        self.quote = None;
+                        if self.depth == 0 {
+                            return Poll::Ready(self.complete(self.scanned));
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+
+            let whitespace = match byte {
+                b' ' | b'\t' | b'\n' | b'\r' => Some(1),
+                _ if self.options.unicode_whitespace && !byte.is_ascii_graphic() => {
+                    match self.unicode_whitespace_at(self.scanned - 1) {
+                        Poll::Ready(whitespace) => whitespace,
+                        Poll::Pending => {
+                            self.scanned -= 1;
+                            return Poll::Pending;
+                        }
+                    }
+                }
+                _ => None,
+            };
+            let is_quote = byte == b'"' || (byte == b'\'' && self.options.allow_single_quotes);
+            // 顶层的数字或字面量在空白或任何结构性字符处结束，该字符留给下一个值
+            let ends_scalar = is_quote
+                || whitespace.is_some()
+                || matches!(byte, b'[' | b'{' | b']' | b'}' | b',' | b':');
+            if self.depth == 0 && self.started && ends_scalar {
+                return Poll::Ready(self.complete(self.scanned - 1));
+            }
+            if let Some(len) = whitespace {
+                self.scanned += len - 1;
+                continue;
+            }
+
+            match byte {
+                _ if is_quote => {
+                    self.started = true;
+                    self.quote = Some(byte);
+                }
+                b'[' | b'{' => {
+                    self.started = true;
+                    self.depth += 1;
+                }
+                b']' | b'}' => {
+                    // 多余的右括号也交给 parse 去报错
+                    self.depth = self.depth.saturating_sub(1);
+                    if self.depth == 0 {
+                        return Poll::Ready(self.complete(self.scanned));
+                    }
+                }
+                _ => self.started = true,
+            }
+        }
+        Poll::Pending
+    }
+
+    /// Parses whatever is left in the buffer at the end of the stream, or
+    /// returns `None` if only whitespace remains
+    pub fn finish(&mut self) -> Option<Result<Value, ParseError>> {
+        if let Poll::Ready(result) = self.feed(&[]) {
+            return Some(result);
+        }
+        if !self.started {
+            return None;
+        }
+        Some(self.complete(self.buffer.len()))
+    }
+
+    /// 从 `start` 开始的字符是 Unicode 空白时给出它的字节数；字符的字节
+    /// 还没有全部到达时返回 `Poll::Pending`
+    fn unicode_whitespace_at(&self, start: usize) -> Poll<Option<usize>> {
+        let bytes = &self.buffer[start..self.buffer.len().min(start + 4)];
+        let valid_up_to = match std::str::from_utf8(bytes) {
+            Ok(_) => bytes.len(),
+            Err(error) if error.valid_up_to() == 0 && error.error_len().is_none() => {
+                return Poll::Pending;
+            }
+            Err(error) => error.valid_up_to(),
+        };
+        let ch = std::str::from_utf8(&bytes[..valid_up_to])
+            .ok()
+            .and_then(|valid| valid.chars().next());
+        Poll::Ready(ch.filter(|ch| ch.is_whitespace()).map(char::len_utf8))
+    }
+
+    fn complete(&mut self, end: usize) -> Result<Value, ParseError> {
+        let result = std::str::from_utf8(&self.buffer[..end])
+            .map_err(ParseError::InvalidUtf8)
+            .and_then(|input| parse_with_options(input, &self.options));
+
+        self.buffer.drain(..end);
+        self.scanned = 0;
+        self.depth = 0;
+        self.quote = None;
+        self.is_escaping = false;
+        self.started = false;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::IncrementalParser;
+    use crate::number::Number;
+    use crate::options::ParseOptions;
+    use crate::value::Value;
+    use std::collections::HashMap;
+    use std::task::Poll;
+
+    #[test]
+    fn waits_for_object_split_across_chunks() {
+        let mut parser = IncrementalParser::new();
+        assert_eq!(parser.feed(br#"{"a":"#), Poll::Pending);
+
+        let mut map = HashMap::new();
+        map.insert("a".into(), Value::Number(Number::I64(1)));
+        assert_eq!(parser.feed(b"1}"), Poll::Ready(Ok(Value::Object(map))));
+    }
+
+    #[test]
+    fn keeps_bytes_after_a_complete_value() {
+        let mut parser = IncrementalParser::new();
+        assert_eq!(
+            parser.feed(br#"["]"] [tr"#),
+            Poll::Ready(Ok(Value::Array(vec![Value::String("]".into())])))
+        );
+        assert_eq!(parser.feed(b"ue"), Poll::Pending);
+        assert_eq!(
+            parser.feed(b"]"),
+            Poll::Ready(Ok(Value::Array(vec![Value::Boolean(true)])))
+        );
+        assert_eq!(parser.finish(), None);
+    }
+
+    #[test]
+    fn finish_flushes_top_level_number() {
+        let mut parser = IncrementalParser::new();
+        assert_eq!(parser.feed(b"12"), Poll::Pending);
+        assert_eq!(parser.feed(b"3"), Poll::Pending);
+        assert_eq!(parser.finish(), Some(Ok(Value::Number(Number::I64(123)))));
+    }
+
+    #[test]
+    fn top_level_scalar_ends_at_structural_character() {
+        let mut parser = IncrementalParser::new();
+        assert_eq!(
+            parser.feed(b"1[2]"),
+            Poll::Ready(Ok(Value::Number(Number::I64(1))))
+        );
+        assert_eq!(
+            parser.feed(b""),
+            Poll::Ready(Ok(Value::Array(vec![Value::Number(Number::I64(2))])))
+        );
+        assert_eq!(
+            parser.feed(br#"true"x""#),
+            Poll::Ready(Ok(Value::Boolean(true)))
+        );
+        assert_eq!(parser.feed(b""), Poll::Ready(Ok(Value::String("x".into()))));
+        assert_eq!(parser.finish(), None);
+    }
+
+    #[test]
+    fn parses_with_options() {
+        let options = ParseOptions {
+            allow_single_quotes: true,
+            ..Default::default()
+        };
+        let mut parser = IncrementalParser::with_options(options);
+        assert_eq!(parser.feed(b"['a]"), Poll::Pending);
+        assert_eq!(
+            parser.feed(b"']"),
+            Poll::Ready(Ok(Value::Array(vec![Value::String("a]".into())])))
+        );
+    }
+
+    #[test]
+    fn top_level_scalar_ends_at_unicode_whitespace_with_option() {
+        let options = ParseOptions {
+            unicode_whitespace: true,
+            ..Default::default()
+        };
+        let mut parser = IncrementalParser::with_options(options);
+        // 不换行空格 U+00A0 是两个字节，这里分两次到达
+        assert_eq!(parser.feed(b"\xc2\xa01\xc2"), Poll::Pending);
+        assert_eq!(
+            parser.feed(b"\xa0true\xe3\x80\x80"),
+            Poll::Ready(Ok(Value::Number(Number::I64(1))))
+        );
+        assert_eq!(parser.feed(b""), Poll::Ready(Ok(Value::Boolean(true))));
+        assert_eq!(parser.finish(), None);
+    }
+}
@@ -0,0 +1,310 @@
+use crate::number::Number;
+use crate::options::ParseOptions;
+use crate::parse::{parse_document_into, unescape_chars, unescape_in_place, TokenParseError, Tree};
+use crate::tokenize::tokenize_located;
+use crate::value::Value;
+use crate::ParseError;
+use std::cell::OnceCell;
+
+/// A JSON value whose object keys are only unescaped once they are read
+///
+/// Returned by [`parse_lazy`]; [`LazyValue::into_value`] turns it into a
+/// regular [`Value`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum LazyValue {
+    Null,
+    Boolean(bool),
+    String(String),
+    Number(Number),
+    Array(Vec<LazyValue>),
+    Object(LazyObject),
+}
+
+/// The members of an object in document order, with their keys kept as
+/// written until first read
+///
+/// Lookups scan the members in order, which suits reading a few keys out of
+/// a large object; when a key appears twice, the later member wins, as in
+/// [`Value::Object`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LazyObject {
+    members: Vec<(LazyKey, LazyValue)>,
+}
+
+/// 保留原文的键，第一次读取时才反转义，并缓存结果
+#[derive(Debug, Clone)]
+struct LazyKey {
+    raw: String,
+    unescaped: OnceCell<String>,
+}
+
+impl LazyKey {
+    fn as_str(&self) -> &str {
+        // 没有反斜杠的键原文就是结果
+        if !self.raw.contains('\\') {
+            return &self.raw;
+        }
+        self.unescaped.get_or_init(|| unescape_key(&self.raw))
+    }
+
+    /// 与 `key` 比较，不分配内存
+    fn matches(&self, key: &str) -> bool {
+        if let Some(unescaped) = self.unescaped.get() {
+            return unescaped == key;
+        }
+        if !self.raw.contains('\\') {
+            return self.raw == key;
+        }
+        // 反转义只会让键变短
+        if key.len() > self.raw.len() {
+            return false;
+        }
+        let mut expected = key.chars();
+        let mut equal = true;
+        let _ = unescape_chars(self.raw.chars(), '"', |ch| {
+            equal &= expected.next() == Some(ch);
+        });
+        equal && expected.next().is_none()
+    }
+
+    fn into_string(self) -> String {
+        if !self.raw.contains('\\') {
+            return self.raw;
+        }
+        match self.unescaped.into_inner() {
+            Some(key) => key,
+            None => unescape_key(&self.raw),
+        }
+    }
+}
+
+fn unescape_key(raw: &str) -> String {
+    let mut key = String::with_capacity(raw.len());
+    // 解析时已经检查过转义序列，这里不会出错
+    let _ = unescape_chars(raw.chars(), '"', |ch| key.push(ch));
+    key
+}
+
+impl PartialEq for LazyKey {
+    fn eq(&self, other: &LazyKey) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl LazyObject {
+    /// The value of member `key`; keys are compared by unescaping them on the
+    /// fly, without allocating
+    pub fn get(&self, key: &str) -> Option<&LazyValue> {
+        self.members
+            .iter()
+            .rev()
+            .find(|(member, _)| member.matches(key))
+            .map(|(_, value)| value)
+    }
+
+    /// The members in document order, unescaping every key
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &LazyValue)> {
+        self.members
+            .iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+impl LazyValue {
+    /// The value of member `key` if this is an object, as with
+    /// [`LazyObject::get`]
+    pub fn get(&self, key: &str) -> Option<&LazyValue> {
+        match self {
+            LazyValue::Object(object) => object.get(key),
+            _ => None,
+        }
+    }
+
+    /// Converts to a [`Value`], unescaping the keys that were never read
+    pub fn into_value(self) -> Value {
+        match self {
+            LazyValue::Null => Value::Null,
+            LazyValue::Boolean(boolean) => Value::Boolean(boolean),
+            LazyValue::String(string) => Value::String(string),
+            LazyValue::Number(number) => Value::Number(number),
+            LazyValue::Array(array) => array.into_iter().map(LazyValue::into_value).collect(),
+            LazyValue::Object(object) => object
+                .members
+                .into_iter()
+                .map(|(key, value)| (key.into_string(), value.into_value()))
+                .collect(),
+        }
+    }
+}
+
+/// Parses like [`crate::parse`], but leaves object keys escaped until they
+/// are read
+///
+/// Keys are still checked for malformed escapes, so the same input fails here
+/// as with `parse`; only the unescaping itself, and building the hash maps,
+/// is deferred. Worth it for documents with many escaped keys of which only a
+/// few are read.
+pub fn parse_lazy(input: &str) -> Result<LazyValue, ParseError> {
+    parse_lazy_with_options(input, &ParseOptions::default())
+}
+
+/// Parses like [`crate::parse_with_options`], deferring key unescaping as
+/// with [`parse_lazy`]
+pub fn parse_lazy_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<LazyValue, ParseError> {
+    let mut tokens = tokenize_located(input, options)?;
+    let value = parse_document_into(&mut tokens, options, &mut LazyTree::default())?;
+    Ok(value)
+}
+
+/// 字符串值照常反转义，键只检查转义序列，保留原文
+#[derive(Debug, Default)]
+struct LazyTree {
+    scratch: String,
+}
+
+impl Tree for LazyTree {
+    type Value = LazyValue;
+    type Key = LazyKey;
+    type Object = LazyObject;
+
+    fn scalar(&mut self, scalar: Value) -> LazyValue {
+        match scalar {
+            Value::Boolean(boolean) => LazyValue::Boolean(boolean),
+            Value::Number(number) => LazyValue::Number(number),
+            _ => LazyValue::Null,
+        }
+    }
+
+    fn string(&mut self, raw: String) -> Result<LazyValue, TokenParseError> {
+        unescape_in_place(raw, &mut self.scratch).map(LazyValue::String)
+    }
+
+    fn array(&mut self, array: Vec<LazyValue>) -> LazyValue {
+        LazyValue::Array(array)
+    }
+
+    fn key(&mut self, raw: String) -> Result<LazyKey, TokenParseError> {
+        unescape_chars(raw.chars(), '"', |_| {})?;
+        Ok(LazyKey {
+            raw,
+            unescaped: OnceCell::new(),
+        })
+    }
+
+    fn key_str(key: &LazyKey) -> &str {
+        key.as_str()
+    }
+
+    fn contains_key(object: &LazyObject, key: &LazyKey) -> bool {
+        object.get(key.as_str()).is_some()
+    }
+
+    fn is_empty(object: &LazyObject) -> bool {
+        object.is_empty()
+    }
+
+    fn insert(object: &mut LazyObject, key: LazyKey, value: LazyValue) {
+        object.members.push((key, value));
+    }
+
+    fn object(&mut self, object: LazyObject) -> LazyValue {
+        LazyValue::Object(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_lazy, parse_lazy_with_options, LazyValue};
+    use crate::number::Number;
+    use crate::options::ParseOptions;
+    use crate::parse::TokenParseError;
+    use crate::tokenize::Token;
+    use crate::{parse, ParseError};
+
+    #[test]
+    fn reads_escaped_keys_on_demand() {
+        let value = parse_lazy(r#"{"a\tb": 1, "cé": [true, "x\n"], "plain": null}"#).unwrap();
+        assert_eq!(value.get("a\tb"), Some(&LazyValue::Number(Number::I64(1))));
+        assert_eq!(
+            value.get("c\u{e9}"),
+            Some(&LazyValue::Array(vec![
+                LazyValue::Boolean(true),
+                LazyValue::String("x\n".into())
+            ]))
+        );
+        assert_eq!(value.get("plain"), Some(&LazyValue::Null));
+        assert_eq!(value.get("a\\tb"), None);
+    }
+
+    #[test]
+    fn later_duplicate_key_wins() {
+        let value = parse_lazy(r#"{"k": 1, "k": 2}"#).unwrap();
+        assert_eq!(value.get("k"), Some(&LazyValue::Number(Number::I64(2))));
+    }
+
+    #[test]
+    fn converts_to_the_parsed_value() {
+        let input = r#"{"x\"y": {"\\": [1, {}]}, "z": [], "s": "é"}"#;
+        assert_eq!(
+            parse_lazy(input).unwrap().into_value(),
+            parse(input).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_what_parse_rejects() {
+        for input in [
+            r#"{"\q": 1}"#,
+            r#"{"\q" 1}"#,
+            r#"{"a": 1,}"#,
+            "{1: 2}",
+            r#"{"a" 1}"#,
+            "[1,]",
+            "[1 2]",
+            "{} {}",
+        ] {
+            assert_eq!(parse_lazy(input).err(), parse(input).err(), "{input}");
+        }
+        assert_eq!(
+            parse_lazy("{1: 2}"),
+            Err(ParseError::TokenParseError(
+                TokenParseError::ExpectedProperty(Token::Number(Number::I64(1)))
+            ))
+        );
+    }
+
+    #[test]
+    fn applies_parse_options() {
+        let options = ParseOptions {
+            reject_duplicate_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_lazy_with_options(r#"{"a": 1, "\u0061": 2}"#, &options),
+            Err(ParseError::TokenParseError(TokenParseError::DuplicateKey(
+                "a".into()
+            )))
+        );
+        let options = ParseOptions {
+            max_depth: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_lazy_with_options(r#"{"a": []}"#, &options),
+            Err(ParseError::TokenParseError(
+                TokenParseError::DepthLimitExceeded
+            ))
+        );
+    }
+}
@@ -1,15 +1,56 @@
+mod base64;
+mod convert;
+mod diff;
+mod incremental;
+mod lazy;
+mod number;
+mod options;
 mod parse;
+mod pointer;
+mod recover;
+mod reuse;
+#[cfg(test)]
+mod roundtrip;
+mod select;
+mod serialize;
+mod shared;
 mod tokenize;
+mod transform;
 mod value;
 
-use crate::parse::{parse_tokens, TokenParseError};
-use crate::tokenize::{tokenize, TokenizeError};
-use crate::value::Value;
+pub use crate::convert::{ConversionError, FromValue, JsonType};
+pub use crate::diff::KeyDiff;
+pub use crate::incremental::IncrementalParser;
+pub use crate::lazy::{parse_lazy, parse_lazy_with_options, LazyObject, LazyValue};
+pub use crate::number::Number;
+pub use crate::options::{ControlCharPolicy, ParseOptions};
+use crate::parse::{parse_document, parse_tokens, TokenParseError};
+pub use crate::pointer::{PointerError, SetError};
+pub use crate::recover::parse_array_recovering;
+pub use crate::reuse::Parser;
+pub use crate::serialize::{LineEnding, NonFinitePolicy, SerializeError, SerializeOptions};
+pub use crate::shared::{parse_shared, parse_shared_with_options, SharedValue};
+pub use crate::tokenize::{
+    token_at, tokenize, tokenize_lenient, tokenize_with_options, tokenize_with_spans, Position,
+    Span, Token, TokenizeError,
+};
+use crate::tokenize::{tokenize_located, tokenize_validating};
+pub use crate::value::Value;
+use std::io::{self, Read};
+use std::str::{FromStr, Utf8Error};
 
 #[derive(Debug, PartialEq)]
 pub enum ParseError {
-    TokenizeError(TokenizeError),
+    /// The input could not be tokenized; the position is where the failing
+    /// token starts
+    TokenizeError(TokenizeError, Position),
     TokenParseError(TokenParseError),
+    /// The input bytes were not valid UTF-8
+    InvalidUtf8(Utf8Error),
+    /// The UTF-16 input contained an unpaired surrogate
+    InvalidUtf16,
+    /// Reading the input failed
+    Io(io::ErrorKind),
 }
 
 impl From<TokenParseError> for ParseError {
@@ -18,28 +59,750 @@ impl From<TokenParseError> for ParseError {
     }
 }
 
-impl From<TokenizeError> for ParseError {
-    fn from(e: TokenizeError) -> Self {
-        Self::TokenizeError(e)
+impl From<(TokenizeError, Position)> for ParseError {
+    fn from((e, position): (TokenizeError, Position)) -> Self {
+        Self::TokenizeError(e, position)
+    }
+}
+
+/// Coarse category of an error, stable across the specific variants of
+/// [`TokenizeError`], `TokenParseError` and [`ParseError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// The input is not well-formed JSON
+    Syntax,
+    /// The input ended before the document was complete
+    UnexpectedEof,
+    /// Reading the input failed
+    Io,
+    /// The input exceeded a configured limit
+    LimitExceeded,
+    /// The input was not valid text in the expected encoding
+    Encoding,
+}
+
+impl ParseError {
+    /// Whether the input was merely cut short, so that appending more input
+    /// could still produce a valid document (as opposed to being malformed
+    /// no matter what follows)
+    pub fn is_recoverable(&self) -> bool {
+        self.kind() == ErrorKind::UnexpectedEof
+    }
+
+    /// The coarse category of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ParseError::TokenizeError(e, _) => e.kind(),
+            ParseError::TokenParseError(e) => e.kind(),
+            ParseError::InvalidUtf8(_) | ParseError::InvalidUtf16 => ErrorKind::Encoding,
+            ParseError::Io(_) => ErrorKind::Io,
+        }
     }
 }
 
 pub fn parse(input: &str) -> Result<Value, ParseError> {
-    let tokens = tokenize(input)?;
-    let value = parse_tokens(&tokens, &mut 0)?;
+    parse_with_options(input, &ParseOptions::default())
+}
+
+pub fn parse_with_options(input: &str, options: &ParseOptions) -> Result<Value, ParseError> {
+    let mut tokens = tokenize_located(input, options)?;
+    let value = parse_document(&mut tokens, options)?;
     Ok(value)
 }
 
+/// Checks whether the input is valid JSON without materializing its strings
+///
+/// Strings are still checked for closing quotes, control characters and
+/// malformed escapes, but are never copied or unescaped, which avoids most
+/// allocations on string-heavy documents.
+pub fn is_valid(input: &str) -> bool {
+    let options = ParseOptions::default();
+    match tokenize_validating(input, &options) {
+        Ok(mut tokens) => parse_document(&mut tokens, &options).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Parses every top-level value of a whitespace-separated stream such as
+/// `1 2 "three" [4]`
+pub fn parse_all(input: &str) -> Result<Vec<Value>, ParseError> {
+    let options = ParseOptions::default();
+    let mut tokens = tokenize_located(input, &options)?;
+
+    let mut index = 0;
+    let mut values = Vec::new();
+    while index < tokens.len() {
+        values.push(parse_tokens(&mut tokens, &mut index, &options)?);
+    }
+    Ok(values)
+}
+
+/// Parses an `application/json-seq` (RFC 7464) stream, in which every record
+/// starts with the U+001E record separator and usually ends with a line feed
+///
+/// Records that contain only whitespace are skipped.
+pub fn parse_json_seq(input: &str) -> impl Iterator<Item = Result<Value, ParseError>> + '_ {
+    input
+        .split('\u{1e}')
+        .filter(|record| !record.trim_matches([' ', '\t', '\n', '\r']).is_empty())
+        .map(parse)
+}
+
+/// Parses UTF-8 encoded bytes, e.g. straight from a socket or file
+///
+/// Bytes that are not valid UTF-8 fail with [`ParseError::InvalidUtf8`];
+/// this includes the three-byte encodings of lone surrogates (`ED A0 80` to
+/// `ED BF BF`), so like a `&str`, the tokenizer never sees a surrogate.
+pub fn parse_bytes(input: &[u8]) -> Result<Value, ParseError> {
+    let input = std::str::from_utf8(input).map_err(ParseError::InvalidUtf8)?;
+    parse(input)
+}
+
+/// Reads `reader` to the end and parses its contents as with [`parse_bytes`]
+pub fn parse_reader(reader: impl Read) -> Result<Value, ParseError> {
+    parse_reader_counted(reader).map(|(value, _)| value)
+}
+
+/// Like [`parse_reader`], but also returns the number of bytes read, e.g. for
+/// progress reporting
+pub fn parse_reader_counted(mut reader: impl Read) -> Result<(Value, usize), ParseError> {
+    let mut bytes = Vec::new();
+    let count = reader
+        .read_to_end(&mut bytes)
+        .map_err(|e| ParseError::Io(e.kind()))?;
+    Ok((parse_bytes(&bytes)?, count))
+}
+
+/// Parses like [`parse`], so that `input.parse::<Value>()` works
+impl FromStr for Value {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        parse(input)
+    }
+}
+
+/// Parses JSON from UTF-16 code units, as produced by Windows APIs or JavaScript strings
+pub fn parse_utf16(input: &[u16]) -> Result<Value, ParseError> {
+    let input = String::from_utf16(input).map_err(|_| ParseError::InvalidUtf16)?;
+    parse(&input)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::number::Number;
+    use std::collections::HashMap;
 
-    #[test]
-    fn parses_test() {
-        let input = r#"
+    const SAMPLE: &str = r#"
         {"basic_info":{"title":"瓜分奖","time_text":"04月-20日 02:00-05月-29日 12:00","start_time":"04.20 02:00:00","activity_id":2199039482869,"attention_text":"权益说明","enroll_type":1,"enroll_info":{"status":" need_enroll"},"activity_type":"terra_divide_reward","order_types":[201,202,203,204,205,208,210,220],"driver_id":580542143947406,"product_level":[260],"origin_status":"not_start","user_city":0,"head_tip":"完成30单\\n参与瓜分{19.2万元}","status":"not_start"},"reward_task":{"restrict_info":{"progress_pancel_v3":{"desc_text":"保持排名，结束后可瓜分{11.2万元}"},"text":"保持排名，结束后可瓜分{11.2万元}","order_type":["快车单","特惠快车单","滴滴特快单","优享单","拼车单","特惠快车抢单模式","特惠快车单（仅轻快司机）","自选车"],"order_type_text":"不包含拼车一口价单","strive_type":["实时单","预约指派订单","预约单抢单接单"],"region_type":["顺路目的地","顺路区域","非顺路订单"],"city_list":["北京市（仅限东城区、西城区）"],"threshold":{"cal_rule":"30%单量+40%流水","rank_rule":[{"percent":"前10%","divide_amount":"{1}万元"},{"percent":"前10-40%","divide_amount":"{2.0001}万元"},{"percent":"前40-100%","divide_amount":"{3}万元"}]},"activity_rank_info":[{"cur_list":[{"stage":1,"rank":1,"score":100.25,"user_id":1,"order_cnt":100,"order_income":10000.01},{"stage":1,"rank":10,"score":90.25,"user_id":10,"order_cnt":50,"order_income":5000.01}],"percent":"排名前10%"},{"cur_list":[{"stage":2,"rank":11,"score":80.25,"user_id":11,"order_cnt":20,"order_income":500.01},{"stage":2,"rank":20,"score":70.25,"user_id":20,"order_cnt":20,"order_income":200.91}],"percent":"排名前10-40%"},{"cur_list":[{"stage":3,"rank":21,"score":50.25,"user_id":21,"order_cnt":10,"order_income":120.01},{"stage":3,"rank":50,"score":30.25,"user_id":50,"order_cnt":1,"order_income":10.91}],"percent":"排名前40-100%"}],"my_rank_info":{"order_cnt":7,"total_income":70,"score":30.1,"rank":3,"cur_stage":1,"cur_stage_amount":10000},"geo_info":{"start":[{"id":"3908367","desc":"起点范围"}],"end":[{"id":"3908367","desc":"起点范围"}]}}}}
         "#;
-        let parsed = parse(input).unwrap();
+
+    #[test]
+    fn parses_test() {
+        let parsed = parse(SAMPLE).unwrap();
         println!("{:?}", parsed);
     }
+
+    #[test]
+    fn indexes_sample_by_key_and_position() {
+        let parsed = parse(SAMPLE).unwrap();
+        assert_eq!(parsed["basic_info"]["title"], "瓜分奖");
+        assert_eq!(parsed["basic_info"]["order_types"][0], 201.0);
+        assert_eq!(
+            parsed["reward_task"]["restrict_info"]["activity_rank_info"][1]["percent"],
+            "排名前10-40%"
+        );
+        assert_eq!(parsed["basic_info"]["missing"]["deeper"], Value::Null);
+        assert_eq!(parsed["basic_info"]["order_types"][99], Value::Null);
+        assert_eq!(parsed[0], Value::Null);
+    }
+
+    #[test]
+    fn sample_integers_round_trip_exactly() {
+        let parsed = parse(SAMPLE).unwrap();
+        assert!(matches!(
+            parsed["basic_info"]["activity_id"],
+            Value::Number(Number::I64(2199039482869))
+        ));
+        assert_eq!(
+            parsed["basic_info"]["activity_id"].to_string(),
+            "2199039482869"
+        );
+
+        // 超过 2^53 的整数用 f64 会丢精度
+        let parsed = parse("[9007199254740993, 18446744073709551615]").unwrap();
+        assert!(matches!(parsed[1], Value::Number(Number::U64(u64::MAX))));
+        assert_eq!(
+            parsed.to_string(),
+            "[9007199254740993,18446744073709551615]"
+        );
+    }
+
+    #[test]
+    fn integers_and_floats_keep_their_representation() {
+        let one = parse("1").unwrap();
+        assert!(matches!(one, Value::Number(Number::I64(1))));
+        assert_eq!(one.to_string(), "1");
+        assert!(matches!(
+            parse("1.0").unwrap(),
+            Value::Number(Number::F64(_))
+        ));
+        assert!(matches!(
+            parse("1e2").unwrap(),
+            Value::Number(Number::F64(_))
+        ));
+        assert!(matches!(
+            parse("-0").unwrap(),
+            Value::Number(Number::F64(_))
+        ));
+    }
+
+    #[test]
+    fn looks_up_sample_by_pointer() {
+        let parsed = parse(SAMPLE).unwrap();
+        assert_eq!(
+            parsed.pointer("/reward_task/restrict_info/my_rank_info/rank"),
+            Some(&Value::Number(Number::I64(3)))
+        );
+        assert_eq!(
+            parsed.pointer("/reward_task/restrict_info/activity_rank_info/2/cur_list/1/user_id"),
+            Some(&Value::Number(Number::I64(50)))
+        );
+        // my_rank_info 在 restrict_info 下面，不是 reward_task 的直接成员
+        assert_eq!(parsed.pointer("/reward_task/my_rank_info/rank"), None);
+        assert_eq!(parsed.pointer("/basic_info/order_types/8"), None);
+        assert_eq!(parsed.pointer("/basic_info/title/0"), None);
+    }
+
+    #[test]
+    fn groups_sample_rank_entries_by_stage() {
+        let parsed = parse(SAMPLE).unwrap();
+        let entries: Value = parsed
+            .select("$.reward_task.restrict_info.activity_rank_info[*].cur_list[*]")
+            .into_iter()
+            .cloned()
+            .collect();
+
+        let groups = entries.group_by("stage").unwrap();
+        let mut stages: Vec<_> = groups.keys().map(String::as_str).collect();
+        stages.sort_unstable();
+        assert_eq!(stages, ["1", "2", "3"]);
+        let ranks: Vec<_> = groups["2"].iter().map(|entry| &entry["rank"]).collect();
+        assert_eq!(ranks, [&11.0, &20.0]);
+
+        assert!(parsed.group_by("stage").is_none());
+    }
+
+    #[test]
+    fn parses_python_non_finite_literals() {
+        let input = r#"{"x": NaN, "y": Infinity}"#;
+        let options = ParseOptions {
+            allow_non_finite_literals: true,
+            ..Default::default()
+        };
+        let value = parse_with_options(input, &options).unwrap();
+
+        let Some(Value::Number(x)) = value.pointer("/x") else {
+            panic!("x is a number");
+        };
+        assert!(x.as_f64().is_nan());
+        assert_eq!(*value.pointer("/y").unwrap(), f64::INFINITY);
+
+        assert!(parse(input).is_err());
+    }
+
+    #[test]
+    fn empty_input_is_error() {
+        let expected = Err(ParseError::TokenParseError(
+            TokenParseError::UnexpectedEndOfInput,
+        ));
+        assert_eq!(parse(""), expected);
+        assert_eq!(parse(" \n\t"), expected);
+        assert!(!is_valid(""));
+    }
+
+    #[test]
+    fn tokenize_error_reports_line_and_column() {
+        let input = "{\n  \"a\": 1,\n  \"b\": @\n}";
+        assert_eq!(
+            parse(input),
+            Err(ParseError::TokenizeError(
+                TokenizeError::CharNotRecognized('@'),
+                Position { line: 3, column: 8 }
+            ))
+        );
+
+        // 未闭合的字符串报告开头引号的位置
+        let Err(ParseError::TokenizeError(_, position)) = parse("[\n\n \"abc") else {
+            panic!("expected a tokenize error");
+        };
+        assert_eq!(position.to_string(), "line 3, column 2");
+
+        // 列按字符计数，而不是按字节
+        let Err(ParseError::TokenizeError(_, position)) = parse("[\"olá\", @]") else {
+            panic!("expected a tokenize error");
+        };
+        assert_eq!(position, Position { line: 1, column: 9 });
+    }
+
+    #[test]
+    fn parses_utf8_bytes() {
+        let parsed = parse_bytes("{\"title\": \"瓜分奖\"}".as_bytes()).unwrap();
+        assert_eq!(parsed["title"], "瓜分奖");
+
+        let Err(ParseError::InvalidUtf8(error)) = parse_bytes(b"[\"a\xff\"]") else {
+            panic!("expected a UTF-8 error");
+        };
+        assert_eq!(error.valid_up_to(), 3);
+    }
+
+    #[test]
+    fn byte_input_rejects_encoded_surrogate() {
+        // U+D800 按 UTF-8 的方式编码后是 ED A0 80
+        let input = b"[\"a\xed\xa0\x80\"]";
+        assert!(matches!(
+            parse_bytes(input),
+            Err(ParseError::InvalidUtf8(_))
+        ));
+        assert!(matches!(
+            parse_reader(&input[..]),
+            Err(ParseError::InvalidUtf8(_))
+        ));
+
+        let mut parser = IncrementalParser::new();
+        assert!(matches!(
+            parser.feed(input),
+            std::task::Poll::Ready(Err(ParseError::InvalidUtf8(_)))
+        ));
+    }
+
+    #[test]
+    fn errors_map_to_kinds() {
+        let kind = |input: &str| parse(input).unwrap_err().kind();
+        assert_eq!(kind("[1 2]"), ErrorKind::Syntax);
+        assert_eq!(kind("@"), ErrorKind::Syntax);
+        assert_eq!(kind("[1,"), ErrorKind::UnexpectedEof);
+        assert_eq!(kind("\"abc"), ErrorKind::UnexpectedEof);
+        let limited = ParseOptions {
+            max_tokens: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_with_options("[1, 2]", &limited).unwrap_err().kind(),
+            ErrorKind::LimitExceeded
+        );
+        assert_eq!(
+            parse_reader(&[0xff][..]).unwrap_err().kind(),
+            ErrorKind::Encoding
+        );
+        assert_eq!(
+            ParseError::Io(io::ErrorKind::BrokenPipe).kind(),
+            ErrorKind::Io
+        );
+    }
+
+    #[test]
+    fn deeply_nested_input_fails_cleanly() {
+        let input = "[".repeat(10_000);
+        assert_eq!(
+            parse(&input),
+            Err(ParseError::TokenParseError(
+                TokenParseError::DepthLimitExceeded
+            ))
+        );
+        assert_eq!(
+            parse(&"{\"a\":".repeat(10_000)).unwrap_err().kind(),
+            ErrorKind::LimitExceeded
+        );
+
+        let unlimited = ParseOptions {
+            max_depth: None,
+            ..Default::default()
+        };
+        let input = format!("{}{}", "[".repeat(200), "]".repeat(200));
+        assert!(parse(&input).is_err());
+        assert!(parse_with_options(&input, &unlimited).is_ok());
+    }
+
+    #[test]
+    fn numeric_key_is_rejected_in_strict_mode() {
+        let Err(ParseError::TokenParseError(error)) = parse("{123: 4}") else {
+            panic!("expected a parse error");
+        };
+        assert_eq!(
+            error,
+            TokenParseError::ExpectedProperty(Token::Number(Number::I64(123)))
+        );
+        assert_eq!(
+            error.to_string(),
+            "expected a string key, found the number 123; object keys must be quoted"
+        );
+        assert_eq!(
+            parse(r#"{"a": 1, null: 2}"#),
+            Err(ParseError::TokenParseError(
+                TokenParseError::ExpectedProperty(Token::Null)
+            ))
+        );
+    }
+
+    #[test]
+    fn parser_skips_whitespace_tokens() {
+        let options = ParseOptions {
+            keep_whitespace: true,
+            ..Default::default()
+        };
+        let input = " {\n  \"a\" : [ 1 , true ] ,\"b\":null\n} \n";
+        assert_eq!(parse_with_options(input, &options), parse(input));
+        assert!(parse_with_options(input, &options).is_ok());
+        assert_eq!(
+            parse_with_options("[1] 2", &options),
+            Err(ParseError::TokenParseError(TokenParseError::TrailingTokens))
+        );
+    }
+
+    #[test]
+    fn trailing_commas_only_with_option() {
+        let lenient = ParseOptions {
+            allow_trailing_commas: true,
+            ..Default::default()
+        };
+        for (input, strict) in [("[1,2,]", "[1,2]"), (r#"{"a":1,}"#, r#"{"a":1}"#)] {
+            assert!(parse(input).is_err(), "{input}");
+            assert_eq!(
+                parse_with_options(input, &lenient),
+                parse(strict),
+                "{input}"
+            );
+        }
+        for input in ["[,]", "[1,,]", "{,}"] {
+            assert!(parse_with_options(input, &lenient).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn parses_through_from_str() {
+        assert_eq!(
+            "[true]".parse::<Value>(),
+            Ok(Value::Array(vec![Value::Boolean(true)]))
+        );
+        let error = "[true".parse::<Value>().unwrap_err();
+        assert!(error.is_recoverable());
+    }
+
+    #[test]
+    fn parses_single_quoted_strings_with_option() {
+        let options = ParseOptions {
+            allow_single_quotes: true,
+            ..Default::default()
+        };
+        let parsed = parse_with_options(r#"{'key': 'it\'s "fine"'}"#, &options).unwrap();
+        assert_eq!(parsed["key"], r#"it's "fine""#);
+        assert!(parse("{'key': 1}").is_err());
+    }
+
+    #[test]
+    fn escaped_single_quote_only_in_single_quoted_strings() {
+        let invalid = Err(ParseError::TokenParseError(TokenParseError::InvalidEscape(
+            '\'',
+        )));
+        assert_eq!(parse(r#""\'""#), invalid);
+        assert!(!is_valid(r#"["\'"]"#));
+
+        let options = ParseOptions {
+            allow_single_quotes: true,
+            ..Default::default()
+        };
+        assert_eq!(parse_with_options(r#""\'""#, &options), invalid);
+        assert_eq!(
+            parse_with_options(r#"'\\\''"#, &options),
+            Ok(Value::String("\\'".into()))
+        );
+    }
+
+    #[test]
+    fn stray_structural_tokens_are_errors() {
+        for (input, token) in [
+            ("]", Token::RightBracket),
+            ("}", Token::RightBrace),
+            (":", Token::Colon),
+            (",", Token::Comma),
+            ("[1, ,]", Token::Comma),
+            (r#"{"a": }"#, Token::RightBrace),
+        ] {
+            assert_eq!(
+                parse(input),
+                Err(ParseError::TokenParseError(
+                    TokenParseError::UnexpectedToken(token)
+                )),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_values() {
+        let trailing = Err(ParseError::TokenParseError(TokenParseError::TrailingTokens));
+        assert_eq!(parse("1 2"), trailing);
+        assert_eq!(parse("null null"), trailing);
+        assert_eq!(parse("true false"), trailing);
+        assert_eq!(parse("{} []"), trailing);
+        assert!(!is_valid("[] []"));
+        assert_eq!(parse("  42  "), Ok(Value::Number(Number::I64(42))));
+    }
+
+    #[test]
+    fn validates_without_materializing_strings() {
+        assert!(is_valid(SAMPLE));
+        assert!(is_valid(r#"{"a\n": ["\u00e9", "\\"]}"#));
+    }
+
+    #[test]
+    fn validation_rejects_malformed_input() {
+        assert!(!is_valid(r#"{"a" 1}"#));
+        assert!(!is_valid(r#"["unclosed]"#));
+        assert!(!is_valid(r#"["\u12"]"#));
+        assert!(!is_valid(r#"["\uZZZZ"]"#));
+        assert!(!is_valid("[\"\t\"]"));
+        assert!(!is_valid(r#"["\q"]"#));
+    }
+
+    #[test]
+    fn rejects_unknown_escapes() {
+        assert_eq!(
+            parse(r#"["\q"]"#),
+            Err(ParseError::TokenParseError(TokenParseError::InvalidEscape(
+                'q'
+            )))
+        );
+        assert_eq!(parse(r#""\/""#), Ok(Value::String("/".into())));
+    }
+
+    #[test]
+    fn parses_all_values_in_stream() {
+        let expected = vec![
+            Value::Number(Number::I64(1)),
+            Value::Number(Number::I64(2)),
+            Value::String("three".into()),
+            Value::Array(vec![Value::Number(Number::I64(4))]),
+        ];
+        assert_eq!(parse_all(r#"1 2 "three" [4]"#), Ok(expected));
+    }
+
+    #[test]
+    fn parses_all_of_empty_stream() {
+        assert_eq!(parse_all("  "), Ok(vec![]));
+    }
+
+    #[test]
+    fn parses_json_seq_records() {
+        let input = "\u{1e}{\"a\":1}\n\u{1e}[true]\n";
+        let mut records = parse_json_seq(input);
+
+        let mut map = HashMap::new();
+        map.insert("a".into(), Value::Number(Number::I64(1)));
+        assert_eq!(records.next(), Some(Ok(Value::Object(map))));
+        assert_eq!(
+            records.next(),
+            Some(Ok(Value::Array(vec![Value::Boolean(true)])))
+        );
+        assert_eq!(records.next(), None);
+    }
+
+    #[test]
+    fn json_seq_reports_bad_record_and_continues() {
+        let input = "\u{1e}[\n\u{1e}null\n";
+        let records: Vec<_> = parse_json_seq(input).collect();
+
+        assert_eq!(records.len(), 2);
+        assert!(records[0].is_err());
+        assert_eq!(records[1], Ok(Value::Null));
+    }
+
+    #[test]
+    fn parse_reader_counts_bytes() {
+        let input = r#"{"title":"瓜分奖"}"#;
+        let (value, count) = parse_reader_counted(input.as_bytes()).unwrap();
+
+        assert_eq!(count, input.len());
+        assert_eq!(parse_reader(input.as_bytes()), Ok(value));
+    }
+
+    #[test]
+    fn parse_reader_reports_io_error() {
+        struct FailingReader;
+
+        impl Read for FailingReader {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::ConnectionReset.into())
+            }
+        }
+
+        assert_eq!(
+            parse_reader(FailingReader),
+            Err(ParseError::Io(io::ErrorKind::ConnectionReset))
+        );
+    }
+
+    #[test]
+    fn parses_utf16_input() {
+        let input: Vec<u16> = "{}".encode_utf16().collect();
+        assert_eq!(parse_utf16(&input), Ok(Value::Object(HashMap::new())));
+    }
+
+    #[test]
+    fn rejects_unpaired_utf16_surrogate() {
+        assert_eq!(parse_utf16(&[0xD800]), Err(ParseError::InvalidUtf16));
+    }
+
+    #[test]
+    fn truncated_input_is_recoverable() {
+        let err = parse(r#"{"a":"#).unwrap_err();
+        assert_eq!(
+            err,
+            ParseError::TokenParseError(TokenParseError::UnexpectedEndOfInput)
+        );
+        assert!(err.is_recoverable());
+        assert!(parse(r#"["unclosed"#).unwrap_err().is_recoverable());
+    }
+
+    #[test]
+    fn malformed_input_is_not_recoverable() {
+        let err = parse("{]").unwrap_err();
+        assert!(!err.is_recoverable());
+    }
+
+    #[test]
+    fn converts_sample_fields() {
+        let Value::Object(root) = parse(SAMPLE).unwrap() else {
+            panic!("sample is an object");
+        };
+        let Some(Value::Object(basic_info)) = root.get("basic_info") else {
+            panic!("basic_info is an object");
+        };
+
+        let order_types = Vec::<f64>::from_value(&basic_info["order_types"]).unwrap();
+        assert_eq!(
+            order_types,
+            [201.0, 202.0, 203.0, 204.0, 205.0, 208.0, 210.0, 220.0]
+        );
+
+        let missing = basic_info.get("missing").unwrap_or(&Value::Null);
+        assert_eq!(Option::<String>::from_value(missing), Ok(None));
+    }
+
+    #[test]
+    fn unicode_whitespace_only_with_option() {
+        let input = "{\u{a0}}";
+        assert_eq!(
+            parse(input),
+            Err(ParseError::TokenizeError(
+                TokenizeError::CharNotRecognized('\u{a0}'),
+                Position { line: 1, column: 2 }
+            ))
+        );
+
+        let options = ParseOptions {
+            unicode_whitespace: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_with_options(input, &options),
+            Ok(Value::Object(HashMap::new()))
+        );
+    }
+
+    #[test]
+    fn selects_with_wildcard() {
+        let parsed = parse(SAMPLE).unwrap();
+        let percents = parsed.select("$.reward_task.restrict_info.activity_rank_info[*].percent");
+        assert_eq!(percents.len(), 3);
+        assert_eq!(*percents[0], "排名前10%");
+        assert_eq!(*percents[1], "排名前10-40%");
+        assert_eq!(*percents[2], "排名前40-100%");
+    }
+
+    #[test]
+    fn selects_with_index() {
+        let parsed = parse(SAMPLE).unwrap();
+        let selected =
+            parsed.select("$.reward_task.restrict_info.activity_rank_info[1].cur_list[0].rank");
+        assert_eq!(selected, [&Value::Number(Number::I64(11))]);
+    }
+
+    #[test]
+    fn truncates_sample_for_logging() {
+        let truncated = parse(SAMPLE).unwrap().truncated(2, 5);
+
+        let order_types = truncated.select("$.basic_info.order_types");
+        let expected = Value::Array(vec![
+            Value::Number(Number::I64(201)),
+            Value::Number(Number::I64(202)),
+            Value::String("... 6 more".into()),
+        ]);
+        assert_eq!(order_types, [&expected]);
+
+        assert_eq!(*truncated.select("$.basic_info.title")[0], "瓜分奖");
+        assert_eq!(*truncated.select("$.basic_info.time_text")[0], "04月-2…");
+    }
+
+    #[test]
+    fn extracts_order_types_as_array_of_f64() {
+        let parsed = parse(SAMPLE).unwrap();
+        let order_types = parsed.select("$.basic_info.order_types")[0];
+        assert_eq!(
+            order_types.as_array_of::<f64>(),
+            Ok(vec![201.0, 202.0, 203.0, 204.0, 205.0, 208.0, 210.0, 220.0])
+        );
+    }
+
+    #[test]
+    fn doubles_every_number_in_sample() {
+        let original = parse(SAMPLE).unwrap();
+        let mut doubled = parse(SAMPLE).unwrap();
+        doubled.map_values(|value| {
+            if let Value::Number(number) = value {
+                *number = Number::F64(number.as_f64() * 2.0);
+            }
+        });
+
+        let sorted = |numbers: Vec<f64>| {
+            let mut numbers = numbers;
+            numbers.sort_by(f64::total_cmp);
+            numbers
+        };
+        assert_eq!(
+            sorted(doubled.numbers().collect()),
+            sorted(original.numbers().map(|number| number * 2.0).collect())
+        );
+        assert_eq!(
+            doubled.select("$.basic_info.order_types")[0].as_array_of::<f64>(),
+            Ok(vec![402.0, 404.0, 406.0, 408.0, 410.0, 416.0, 420.0, 440.0])
+        );
+
+        let is_container = |value: &Value| matches!(value, Value::Array(_) | Value::Object(_));
+        let containers = |value: &Value| {
+            let mut paths: Vec<_> = value
+                .find_all(is_container)
+                .into_iter()
+                .map(|(path, _)| path)
+                .collect();
+            paths.sort();
+            paths
+        };
+        assert_eq!(containers(&doubled), containers(&original));
+    }
+
+    #[test]
+    fn finds_all_strings_in_sample() {
+        let parsed = parse(SAMPLE).unwrap();
+        let strings = parsed.find_all(|value| matches!(value, Value::String(_)));
+        assert_eq!(strings.len(), 41);
+
+        let title = strings
+            .iter()
+            .find(|(path, _)| path == "/basic_info/title")
+            .map(|(_, value)| *value);
+        assert_eq!(title, Some(&Value::String("瓜分奖".into())));
+    }
 }
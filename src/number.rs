@@ -0,0 +1,128 @@
+use std::fmt;
+
+/// A JSON number, kept as an integer when it has an exact integer value so
+/// that large ids survive beyond the 2^53 precision of `f64`
+#[derive(Clone, Copy)]
+pub enum Number {
+    /// A negative integer, or any integer that fits in an `i64`
+    I64(i64),
+    /// A positive integer above `i64::MAX`
+    U64(u64),
+    /// Any other number
+    F64(f64),
+}
+
+impl Number {
+    /// The value as a float, possibly rounded for integers beyond 2^53
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::I64(int) => int as f64,
+            Number::U64(int) => int as f64,
+            Number::F64(float) => float,
+        }
+    }
+
+    pub fn is_finite(self) -> bool {
+        match self {
+            Number::F64(float) => float.is_finite(),
+            _ => true,
+        }
+    }
+
+    fn as_i128(self) -> Option<i128> {
+        match self {
+            Number::I64(int) => Some(int.into()),
+            Number::U64(int) => Some(int.into()),
+            Number::F64(_) => None,
+        }
+    }
+}
+
+/// Compares by numeric value, so `I64(16)` equals `F64(16.0)`
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        match (self.as_i128(), other.as_i128()) {
+            (Some(a), Some(b)) => a == b,
+            (None, None) => self.as_f64() == other.as_f64(),
+            (Some(int), None) => float_equals_int(other.as_f64(), int),
+            (None, Some(int)) => float_equals_int(self.as_f64(), int),
+        }
+    }
+}
+
+fn float_equals_int(float: f64, int: i128) -> bool {
+    // 超出 i128 范围的浮点数转换时会饱和，先排除
+    float.fract() == 0.0 && float.abs() < 2f64.powi(64) && float as i128 == int
+}
+
+impl From<f64> for Number {
+    fn from(float: f64) -> Self {
+        Number::F64(float)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(int: i64) -> Self {
+        Number::I64(int)
+    }
+}
+
+impl From<u64> for Number {
+    fn from(int: u64) -> Self {
+        match i64::try_from(int) {
+            Ok(int) => Number::I64(int),
+            Err(_) => Number::U64(int),
+        }
+    }
+}
+
+/// Integers print without a fraction, floats like `f64`'s `Debug`, e.g. `16`
+/// and `16.0`
+impl fmt::Debug for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::I64(int) => write!(f, "{int}"),
+            Number::U64(int) => write!(f, "{int}"),
+            Number::F64(float) => write!(f, "{float:?}"),
+        }
+    }
+}
+
+/// Prints the number as it appears in JSON, e.g. `16` for both `I64(16)` and
+/// `F64(16.0)`
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::I64(int) => write!(f, "{int}"),
+            Number::U64(int) => write!(f, "{int}"),
+            Number::F64(float) => write!(f, "{float}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Number;
+
+    #[test]
+    fn compares_across_representations() {
+        assert_eq!(Number::I64(16), Number::F64(16.0));
+        assert_eq!(Number::U64(u64::MAX), Number::U64(u64::MAX));
+        assert_ne!(Number::I64(1), Number::F64(1.5));
+        assert_ne!(Number::U64(u64::MAX), Number::F64(f64::INFINITY));
+        assert_ne!(Number::F64(f64::NAN), Number::F64(f64::NAN));
+    }
+
+    #[test]
+    fn u64_prefers_i64() {
+        assert!(matches!(Number::from(5u64), Number::I64(5)));
+        assert!(matches!(Number::from(u64::MAX), Number::U64(u64::MAX)));
+    }
+
+    #[test]
+    fn formats_integers_without_fraction() {
+        assert_eq!(Number::I64(-3).to_string(), "-3");
+        assert_eq!(format!("{:?}", Number::I64(16)), "16");
+        assert_eq!(format!("{:?}", Number::F64(16.0)), "16.0");
+    }
+}
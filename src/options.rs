@@ -0,0 +1,106 @@
+/// Switches that make parsing more lenient or more strict than plain JSON
+///
+/// The defaults give spec-compliant behavior.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptions {
+    /// Accept any Unicode whitespace (such as U+00A0 no-break space) between
+    /// tokens, not only space, tab, line feed and carriage return
+    pub unicode_whitespace: bool,
+    /// Reject objects whose keys are not unique and in ascending order, for
+    /// verifying that input is already in canonical form
+    ///
+    /// Keys are compared by their unescaped bytes.
+    pub require_sorted_unique_keys: bool,
+    /// Fail with `TokenParseError::DuplicateKey` when an object repeats a key,
+    /// instead of keeping the last value
+    ///
+    /// Keys are compared after unescaping, so `"a"` and `"\u0061"` collide.
+    pub reject_duplicate_keys: bool,
+    /// What to do with control characters that appear unescaped inside strings
+    pub control_char_policy: ControlCharPolicy,
+    /// Fail on a leading U+FEFF byte order mark instead of skipping it
+    pub reject_bom: bool,
+    /// Accept the bare `NaN`, `Infinity` and `-Infinity` keywords written by
+    /// Python's `json` module, as the corresponding `f64` values
+    pub allow_non_finite_literals: bool,
+    /// Locale mode: accept `,` as a thousands separator inside numbers, e.g.
+    /// `1,000.50` as `1000.5`
+    ///
+    /// A comma only counts as a separator when it follows a group of one to
+    /// three integer digits (three after an earlier separator) and is followed
+    /// by exactly three digits. This is inherently ambiguous: `[1,000]` reads
+    /// as the single number `1000`, not as `[1, 0]`, so only enable it for
+    /// data where numbers are never adjacent array elements or values
+    /// followed by a three-digit number.
+    pub allow_thousands_separators: bool,
+    /// Accept a comma right before a closing `]` or `}`, as in `[1, 2,]`,
+    /// which hand-edited config files often contain
+    ///
+    /// Empty elements such as `[1,,2]` or `[,]` are still rejected.
+    pub allow_trailing_commas: bool,
+    /// JSONC: skip `// ...` line comments and `/* ... */` block comments
+    /// between tokens, as in editor config files
+    ///
+    /// Comments are dropped even with `keep_whitespace`.
+    pub allow_comments: bool,
+    /// JSON5: accept strings in single quotes, such as `'hello'`, in which
+    /// `\'` escapes a single quote and `"` needs no escape
+    pub allow_single_quotes: bool,
+    /// Fail with `TokenizeError::TooManyTokens` once the input has produced
+    /// more than this many tokens, bounding the work done on huge but shallow
+    /// input before the parser even runs
+    pub max_tokens: Option<usize>,
+    /// Fail with `TokenParseError::DepthLimitExceeded` once arrays and
+    /// objects nest deeper than this, so that adversarial input such as
+    /// thousands of `[` cannot overflow the stack; defaults to
+    /// [`ParseOptions::DEFAULT_MAX_DEPTH`], `None` removes the limit
+    pub max_depth: Option<usize>,
+    /// Emit every run of whitespace between tokens as a `Token::Whitespace`,
+    /// for tools that rewrite a document while preserving its layout
+    ///
+    /// The parser skips these tokens. They count towards `max_tokens`.
+    pub keep_whitespace: bool,
+}
+
+impl ParseOptions {
+    /// Default for [`ParseOptions::max_depth`]
+    pub const DEFAULT_MAX_DEPTH: usize = 128;
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            unicode_whitespace: false,
+            require_sorted_unique_keys: false,
+            reject_duplicate_keys: false,
+            control_char_policy: ControlCharPolicy::default(),
+            reject_bom: false,
+            allow_non_finite_literals: false,
+            allow_thousands_separators: false,
+            allow_trailing_commas: false,
+            allow_comments: false,
+            allow_single_quotes: false,
+            max_tokens: None,
+            max_depth: Some(Self::DEFAULT_MAX_DEPTH),
+            keep_whitespace: false,
+        }
+    }
+}
+
+/// How the tokenizer treats a raw control character (U+0000 to U+001F)
+/// inside a string, which JSON only allows in escaped form
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCharPolicy {
+    /// Fail with `TokenizeError::UnescapedControlCharacter`
+    #[default]
+    Error,
+    /// Drop the character from the string
+    ///
+    /// Characters are dropped before escapes are read, so a backslash
+    /// followed by a raw tab escapes whatever comes after the tab: `"\<TAB>n"`
+    /// is a line feed, and in `"\<TAB>""` the second quote is escaped rather
+    /// than closing the string.
+    Strip,
+    /// Keep the character in the string as-is
+    Allow,
+}
@@ -1,6 +1,10 @@
+use crate::options::ParseOptions;
 use crate::tokenize::Token;
 use crate::value::Value;
+use crate::ErrorKind;
 use std::collections::HashMap;
+use std::fmt;
+use std::mem;
 
 #[derive(Debug, PartialEq)]
 pub enum TokenParseError {
@@ -10,15 +14,165 @@ pub enum TokenParseError {
     InvalidHexValue,
     /// Unicode 值无效
     InvalidCodePointValue,
+    /// 反斜杠后面的字符不是 JSON 允许的转义，例如 `\q`
+    InvalidEscape(char),
     ExpectedComma,
-    ExpectedProperty,
+    /// 对象中应该是键（字符串）的位置出现了其他 token，例如 `{123: 4}` 中的数字
+    ExpectedProperty(Token),
     ExpectedColon,
+    /// 输入在值完整之前就结束了
+    UnexpectedEndOfInput,
+    /// 对象的键没有严格按字典序排列（包括重复的键）
+    KeysNotCanonical(String),
+    /// 完整的值之后还有多余的 token
+    TrailingTokens,
+    /// 应该是值的位置出现了结构性 token，例如 `]` 或 `,`
+    UnexpectedToken(Token),
+    /// 数组和对象的嵌套层数超过了 `ParseOptions::max_depth`
+    DepthLimitExceeded,
+    /// 同一个对象中重复出现的键
+    DuplicateKey(String),
+}
+
+impl TokenParseError {
+    /// The coarse category of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            TokenParseError::UnexpectedEndOfInput => ErrorKind::UnexpectedEof,
+            TokenParseError::DepthLimitExceeded => ErrorKind::LimitExceeded,
+            _ => ErrorKind::Syntax,
+        }
+    }
+}
+
+impl fmt::Display for TokenParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenParseError::UnfinishedEscape => {
+                f.write_str("\\u escape with fewer than 4 hex digits")
+            }
+            TokenParseError::InvalidHexValue => f.write_str("invalid hex digit in \\u escape"),
+            TokenParseError::InvalidCodePointValue => f.write_str("invalid Unicode code point"),
+            TokenParseError::InvalidEscape(ch) => write!(f, "invalid escape sequence `\\{ch}`"),
+            TokenParseError::ExpectedComma => f.write_str("expected `,` or a closing bracket"),
+            TokenParseError::ExpectedProperty(Token::Number(number)) => write!(
+                f,
+                "expected a string key, found the number {number}; object keys must be quoted"
+            ),
+            TokenParseError::ExpectedProperty(token) => {
+                write!(f, "expected a string key, found {token:?}")
+            }
+            TokenParseError::ExpectedColon => f.write_str("expected `:` after an object key"),
+            TokenParseError::UnexpectedEndOfInput => f.write_str("unexpected end of input"),
+            TokenParseError::KeysNotCanonical(key) => {
+                write!(f, "key {key:?} is duplicated or out of order")
+            }
+            TokenParseError::TrailingTokens => f.write_str("unexpected input after the value"),
+            TokenParseError::UnexpectedToken(token) => {
+                write!(f, "expected a value, found {token:?}")
+            }
+            TokenParseError::DepthLimitExceeded => f.write_str("nesting too deep"),
+            TokenParseError::DuplicateKey(key) => write!(f, "duplicate key {key:?}"),
+        }
+    }
 }
 
 type ParseResult = Result<Value, TokenParseError>;
 
-pub fn parse_tokens(tokens: &[Token], index: &mut usize) -> ParseResult {
-    let token = &tokens[*index];
+/// 解析器构造结果的方式，[`Value`]、`SharedValue` 和 `LazyValue` 共用同一套解析逻辑
+pub(crate) trait Tree {
+    type Value;
+    type Key;
+    type Object: Default;
+
+    /// 字符串以外的标量，即 `null`、布尔值和数字
+    fn scalar(&mut self, scalar: Value) -> Self::Value;
+    /// `raw` 是从字符串 token 中取走的、还没有反转义的原文
+    fn string(&mut self, raw: String) -> Result<Self::Value, TokenParseError>;
+    fn array(&mut self, array: Vec<Self::Value>) -> Self::Value;
+    fn key(&mut self, raw: String) -> Result<Self::Key, TokenParseError>;
+    fn key_str(key: &Self::Key) -> &str;
+    fn contains_key(object: &Self::Object, key: &Self::Key) -> bool;
+    fn is_empty(object: &Self::Object) -> bool;
+    fn insert(object: &mut Self::Object, key: Self::Key, value: Self::Value);
+    fn object(&mut self, object: Self::Object) -> Self::Value;
+}
+
+/// 构造普通的 [`Value`]
+#[derive(Debug, Default)]
+pub(crate) struct ValueTree {
+    /// 反转义用的缓冲区，`Parser` 在多个文档间复用它
+    scratch: String,
+}
+
+impl Tree for ValueTree {
+    type Value = Value;
+    type Key = String;
+    type Object = HashMap<String, Value>;
+
+    fn scalar(&mut self, scalar: Value) -> Value {
+        scalar
+    }
+
+    fn string(&mut self, raw: String) -> ParseResult {
+        unescape_in_place(raw, &mut self.scratch).map(Value::String)
+    }
+
+    fn array(&mut self, array: Vec<Value>) -> Value {
+        Value::Array(array)
+    }
+
+    fn key(&mut self, raw: String) -> Result<String, TokenParseError> {
+        unescape_in_place(raw, &mut self.scratch)
+    }
+
+    fn key_str(key: &String) -> &str {
+        key
+    }
+
+    fn contains_key(object: &HashMap<String, Value>, key: &String) -> bool {
+        object.contains_key(key)
+    }
+
+    fn is_empty(object: &HashMap<String, Value>) -> bool {
+        object.is_empty()
+    }
+
+    fn insert(object: &mut HashMap<String, Value>, key: String, value: Value) {
+        object.insert(key, value);
+    }
+
+    fn object(&mut self, object: HashMap<String, Value>) -> Value {
+        Value::Object(object)
+    }
+}
+
+/// Parses the value starting at `index`, taking the strings out of `tokens`
+/// instead of copying them
+pub fn parse_tokens(
+    tokens: &mut [Token],
+    index: &mut usize,
+    options: &ParseOptions,
+) -> ParseResult {
+    parse_value(tokens, index, options, 0, &mut ValueTree::default())
+}
+
+/// `depth` 是外层已经打开的数组和对象的个数
+fn parse_value<T: Tree>(
+    tokens: &mut [Token],
+    index: &mut usize,
+    options: &ParseOptions,
+    depth: usize,
+    tree: &mut T,
+) -> Result<T::Value, TokenParseError> {
+    let token = peek(tokens, index)?;
+    if matches!(token, Token::LeftBracket | Token::LeftBrace)
+        && options
+            .max_depth
+            .is_some_and(|max_depth| depth >= max_depth)
+    {
+        return Err(TokenParseError::DepthLimitExceeded);
+    }
     if matches!(
         token,
         Token::Null | Token::False | Token::True | Token::Number(_) | Token::String(_)
@@ -26,73 +180,161 @@ pub fn parse_tokens(tokens: &[Token], index: &mut usize) -> ParseResult {
         *index += 1;
     }
     match token {
-        Token::Null => Ok(Value::Null),
-        Token::False => Ok(Value::Boolean(false)),
-        Token::True => Ok(Value::Boolean(true)),
-        Token::Number(number) => Ok(Value::Number(*number)),
-        Token::String(string) => parse_string(string),
-        Token::LeftBracket => parse_array(tokens, index),
-        Token::LeftBrace => parse_object(tokens, index),
-        _ => todo!(),
+        Token::Null => Ok(tree.scalar(Value::Null)),
+        Token::False => Ok(tree.scalar(Value::Boolean(false))),
+        Token::True => Ok(tree.scalar(Value::Boolean(true))),
+        Token::Number(number) => Ok(tree.scalar(Value::Number(*number))),
+        Token::String(_) => tree.string(take_raw(&mut tokens[*index - 1])),
+        Token::LeftBracket => parse_array(tokens, index, options, depth + 1, tree),
+        Token::LeftBrace => parse_object(tokens, index, options, depth + 1, tree),
+        other => Err(TokenParseError::UnexpectedToken(other.clone())),
+    }
+}
+
+/// 解析一个完整的文档：值之后不能再有任何 token
+pub(crate) fn parse_document(tokens: &mut [Token], options: &ParseOptions) -> ParseResult {
+    parse_document_into(tokens, options, &mut ValueTree::default())
+}
+
+/// 同 [`parse_document`]，但由 `tree` 构造结果
+pub(crate) fn parse_document_into<T: Tree>(
+    tokens: &mut [Token],
+    options: &ParseOptions,
+    tree: &mut T,
+) -> Result<T::Value, TokenParseError> {
+    let mut index = 0;
+    let value = parse_value(tokens, &mut index, options, 0, tree)?;
+    skip_whitespace(tokens, &mut index);
+    if index < tokens.len() {
+        return Err(TokenParseError::TrailingTokens);
     }
+    Ok(value)
 }
 
-fn parse_string(input: &str) -> ParseResult {
-    let unescaped = unescape_string(input)?;
-    Ok(Value::String(unescaped))
+/// 跳过空白 token，返回下一个有意义的 token
+pub(crate) fn peek<'a>(
+    tokens: &'a [Token],
+    index: &mut usize,
+) -> Result<&'a Token, TokenParseError> {
+    skip_whitespace(tokens, index);
+    tokens
+        .get(*index)
+        .ok_or(TokenParseError::UnexpectedEndOfInput)
 }
 
-fn unescape_string(input: &str) -> Result<String, TokenParseError> {
-    let mut output = String::new();
+/// 只有 `ParseOptions::keep_whitespace` 打开时才会出现空白 token
+fn skip_whitespace(tokens: &[Token], index: &mut usize) {
+    while let Some(Token::Whitespace(_)) = tokens.get(*index) {
+        *index += 1;
+    }
+}
+
+/// 取走 token 中还没有反转义的字符串
+fn take_raw(token: &mut Token) -> String {
+    let Token::String(string) = token else {
+        unreachable!("take_raw called on {token:?}");
+    };
+    mem::take(string)
+}
 
+/// 反转义 `string`；反转义后的字符串不会比原文长，所以借 `scratch`
+/// 反转义后写回原来的缓冲区不需要重新分配
+pub(crate) fn unescape_in_place(
+    mut string: String,
+    scratch: &mut String,
+) -> Result<String, TokenParseError> {
+    // 没有反斜杠的字符串（绝大多数键）无需逐字符解析
+    if string.contains('\\') {
+        scratch.clear();
+        unescape_chars(string.chars(), '"', |ch| scratch.push(ch))?;
+        string.clear();
+        string.push_str(scratch);
+    }
+    Ok(string)
+}
+
+/// 解析转义序列，把得到的每个字符交给 `push`；`quote` 是字符串的引号，
+/// 只有单引号字符串才允许 `\'`
+pub(crate) fn unescape_chars(
+    mut chars: impl Iterator<Item = char>,
+    quote: char,
+    mut push: impl FnMut(char),
+) -> Result<(), TokenParseError> {
     let mut is_escaping = false;
-    let mut chars = input.chars();
     while let Some(next_char) = chars.next() {
         if is_escaping {
             match next_char {
-                '"' => output.push('"'),
-                '\\' => output.push('\\'),
-                'b' => output.push('\u{8}'),
-                'f' => output.push('\u{12}'),
-                'n' => output.push('\n'),
-                'r' => output.push('\r'),
-                't' => output.push('\t'),
+                '"' => push('"'),
+                '\\' => push('\\'),
+                '/' => push('/'),
+                '\'' if quote == '\'' => push('\''),
+                'b' => push('\u{8}'),
+                'f' => push('\u{c}'),
+                'n' => push('\n'),
+                'r' => push('\r'),
+                't' => push('\t'),
                 'u' => {
-                    let mut sum = 0;
-                    for i in 0..4 {
-                        let next_char = chars.next().ok_or(TokenParseError::UnfinishedEscape)?;
-                        let digit = next_char
-                            .to_digit(16)
-                            .ok_or(TokenParseError::InvalidHexValue)?;
-                        sum += (16u32).pow(3 - i) * digit;
+                    let mut code_point = read_hex4(&mut chars)?;
+                    // 高位代理必须紧跟一个 \u 低位代理，两者合成一个字符
+                    if (0xD800..=0xDBFF).contains(&code_point) {
+                        if chars.next() != Some('\\') || chars.next() != Some('u') {
+                            return Err(TokenParseError::InvalidCodePointValue);
+                        }
+                        let low = read_hex4(&mut chars)?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(TokenParseError::InvalidCodePointValue);
+                        }
+                        code_point = 0x10000 + ((code_point - 0xD800) << 10) + (low - 0xDC00);
                     }
                     let unescaped_char =
-                        char::from_u32(sum).ok_or(TokenParseError::InvalidCodePointValue)?;
-                    output.push(unescaped_char);
+                        char::from_u32(code_point).ok_or(TokenParseError::InvalidCodePointValue)?;
+                    push(unescaped_char);
                 }
-                _ => output.push(next_char),
+                other => return Err(TokenParseError::InvalidEscape(other)),
             }
             is_escaping = false;
         } else if next_char == '\\' {
             is_escaping = true;
         } else {
-            output.push(next_char);
+            push(next_char);
         }
     }
-    Ok(output)
+    Ok(())
+}
+
+/// 读取 `\u` 之后的4个十六进制数字
+fn read_hex4(chars: &mut impl Iterator<Item = char>) -> Result<u32, TokenParseError> {
+    let mut sum = 0;
+    for _ in 0..4 {
+        let next_char = chars.next().ok_or(TokenParseError::UnfinishedEscape)?;
+        let digit = next_char
+            .to_digit(16)
+            .ok_or(TokenParseError::InvalidHexValue)?;
+        sum = sum * 16 + digit;
+    }
+    Ok(sum)
 }
 
-fn parse_array(tokens: &[Token], index: &mut usize) -> ParseResult {
+fn parse_array<T: Tree>(
+    tokens: &mut [Token],
+    index: &mut usize,
+    options: &ParseOptions,
+    depth: usize,
+    tree: &mut T,
+) -> Result<T::Value, TokenParseError> {
     let mut array = Vec::new();
     loop {
         *index += 1;
-        if tokens[*index] == Token::RightBracket {
+        // 逗号后面紧跟 `]` 只有在宽松模式下才允许
+        if *peek(tokens, index)? == Token::RightBracket
+            && (array.is_empty() || options.allow_trailing_commas)
+        {
             break;
         }
-        let value = parse_tokens(tokens, index)?;
+        let value = parse_value(tokens, index, options, depth, tree)?;
         array.push(value);
 
-        let token = &tokens[*index];
+        let token = peek(tokens, index)?;
         match token {
             Token::Comma => {}
             Token::RightBracket => break,
@@ -100,56 +342,103 @@ fn parse_array(tokens: &[Token], index: &mut usize) -> ParseResult {
         }
     }
     *index += 1;
-    Ok(Value::Array(array))
+    Ok(tree.array(array))
 }
 
-fn parse_object(tokens: &[Token], index: &mut usize) -> ParseResult {
-    let mut map = HashMap::new();
+fn parse_object<T: Tree>(
+    tokens: &mut [Token],
+    index: &mut usize,
+    options: &ParseOptions,
+    depth: usize,
+    tree: &mut T,
+) -> Result<T::Value, TokenParseError> {
+    let mut map = T::Object::default();
+    let mut previous_key: Option<String> = None;
 
     loop {
         // 消费逗号和左括号
         *index += 1;
-        if tokens[*index] == Token::RightBrace {
+        if *peek(tokens, index)? == Token::RightBrace
+            && (T::is_empty(&map) || options.allow_trailing_commas)
+        {
             break;
         }
-        if let Token::String(s) = &tokens[*index] {
+        if let Token::String(_) = peek(tokens, index)? {
+            let key_index = *index;
             *index += 1;
-            if Token::Colon == tokens[*index] {
+            if Token::Colon == *peek(tokens, index)? {
                 *index += 1;
-                let key = unescape_string(s)?;
-                let value = parse_tokens(tokens, index)?;
-                map.insert(key, value);
+                let key = tree.key(take_raw(&mut tokens[key_index]))?;
+                if options.require_sorted_unique_keys {
+                    let key = T::key_str(&key);
+                    if previous_key
+                        .as_deref()
+                        .is_some_and(|previous| previous >= key)
+                    {
+                        return Err(TokenParseError::KeysNotCanonical(key.to_owned()));
+                    }
+                    previous_key = Some(key.to_owned());
+                }
+                if options.reject_duplicate_keys && T::contains_key(&map, &key) {
+                    return Err(TokenParseError::DuplicateKey(T::key_str(&key).to_owned()));
+                }
+                let value = parse_value(tokens, index, options, depth, tree)?;
+                T::insert(&mut map, key, value);
             } else {
                 return Err(TokenParseError::ExpectedColon);
             }
             // 在键值对后面的是 Comma 或 RightBrace
-            match &tokens[*index] {
+            match peek(tokens, index)? {
                 Token::Comma => {}
                 Token::RightBrace => break,
                 _ => return Err(TokenParseError::ExpectedComma),
             }
         } else {
-            return Err(TokenParseError::ExpectedProperty);
+            return Err(TokenParseError::ExpectedProperty(
+                peek(tokens, index)?.clone(),
+            ));
         }
     }
     // 消费右括号
     *index += 1;
 
-    Ok(Value::Object(map))
+    Ok(tree.object(map))
 }
 #[cfg(test)]
 mod tests {
+    use crate::number::Number;
+    use crate::options::ParseOptions;
     use crate::tokenize::Token;
     use crate::value::Value;
     use std::collections::HashMap;
 
-    use super::parse_tokens;
+    use super::{parse_document, parse_tokens, ParseResult, TokenParseError};
 
     fn check(input: &[Token], expected: Value) {
-        let actual = parse_tokens(input, &mut 0).unwrap();
+        let actual = parse_tokens(&mut input.to_vec(), &mut 0, &ParseOptions::default()).unwrap();
         assert_eq!(actual, expected);
     }
 
+    fn check_with_options(input: &[Token], options: &ParseOptions, expected: ParseResult) {
+        let actual = parse_tokens(&mut input.to_vec(), &mut 0, options);
+        assert_eq!(actual, expected);
+    }
+
+    fn object_with_keys(keys: &[&str]) -> Vec<Token> {
+        let mut tokens = vec![Token::LeftBrace];
+        for key in keys {
+            tokens.extend([
+                Token::String((*key).into()),
+                Token::Colon,
+                Token::Null,
+                Token::Comma,
+            ]);
+        }
+        tokens.pop();
+        tokens.push(Token::RightBrace);
+        tokens
+    }
+
     #[test]
     fn parses_null() {
         check(&[Token::Null], Value::Null);
@@ -184,6 +473,23 @@ mod tests {
         check(&input, expected);
     }
     #[test]
+    fn parses_string_surrogate_pair() {
+        let input = [Token::String(r"\uD83D\uDCA9 \ud83d\udca9".into())];
+        let expected = Value::String(String::from("💩 💩"));
+
+        check(&input, expected);
+    }
+    #[test]
+    fn rejects_unpaired_surrogates() {
+        for escaped in [r"\uD83D", r"\uD83Dx", r"\uD83D\u0041", r"\uDCA9"] {
+            check_with_options(
+                &[Token::String(escaped.into())],
+                &ParseOptions::default(),
+                Err(TokenParseError::InvalidCodePointValue),
+            );
+        }
+    }
+    #[test]
     fn parses_string_unescape_backslash() {
         let input = [Token::String(r#"hello\\workd"#.into())];
         let expected = Value::String(r#"hello\workd"#.into());
@@ -201,13 +507,76 @@ mod tests {
             Token::LeftBracket,
             Token::Null,
             Token::Comma,
-            Token::Number(16.0),
+            Token::Number(Number::I64(16)),
             Token::RightBracket,
         ];
-        let expected = Value::Array(vec![Value::Null, Value::Number(16.0)]);
+        let expected = Value::Array(vec![Value::Null, Value::Number(Number::I64(16))]);
         check(&input, expected);
     }
     #[test]
+    fn empty_token_stream_is_error() {
+        check_with_options(
+            &[],
+            &ParseOptions::default(),
+            Err(TokenParseError::UnexpectedEndOfInput),
+        );
+    }
+    #[test]
+    fn document_rejects_trailing_tokens() {
+        let options = ParseOptions::default();
+        let mut input = [Token::Number(Number::I64(1)), Token::Number(Number::I64(2))];
+        assert_eq!(
+            parse_document(&mut input, &options),
+            Err(TokenParseError::TrailingTokens)
+        );
+        let mut input = [Token::LeftBrace, Token::RightBrace, Token::RightBrace];
+        assert_eq!(
+            parse_document(&mut input, &options),
+            Err(TokenParseError::TrailingTokens)
+        );
+        assert_eq!(
+            parse_document(&mut [Token::Null], &options),
+            Ok(Value::Null)
+        );
+    }
+    #[test]
+    fn nesting_up_to_max_depth() {
+        let options = ParseOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        let mut input = [
+            Token::LeftBracket,
+            Token::LeftBrace,
+            Token::RightBrace,
+            Token::RightBracket,
+        ];
+        assert!(parse_document(&mut input, &options).is_ok());
+        let mut input = [
+            Token::LeftBracket,
+            Token::LeftBrace,
+            Token::String("a".into()),
+            Token::Colon,
+            Token::LeftBracket,
+            Token::RightBracket,
+            Token::RightBrace,
+            Token::RightBracket,
+        ];
+        assert_eq!(
+            parse_document(&mut input, &options),
+            Err(TokenParseError::DepthLimitExceeded)
+        );
+        // 标量不算一层
+        let mut input = [
+            Token::LeftBracket,
+            Token::LeftBracket,
+            Token::Null,
+            Token::RightBracket,
+            Token::RightBracket,
+        ];
+        assert!(parse_document(&mut input, &options).is_ok());
+    }
+    #[test]
     fn parses_empty_array() {
         let input = [Token::LeftBracket, Token::RightBracket];
         let expected = Value::Array(vec![]);
@@ -250,7 +619,28 @@ mod tests {
     }
 
     #[test]
-    fn parses_object_one_element_comma_end() {
+    fn rejects_object_one_element_comma_end() {
+        let input = [
+            Token::LeftBrace,
+            Token::String("key".into()),
+            Token::Colon,
+            Token::String("value".into()),
+            Token::Comma,
+            Token::RightBrace,
+        ];
+        check_with_options(
+            &input,
+            &ParseOptions::default(),
+            Err(TokenParseError::ExpectedProperty(Token::RightBrace)),
+        );
+    }
+
+    #[test]
+    fn parses_object_one_element_comma_end_when_allowed() {
+        let options = ParseOptions {
+            allow_trailing_commas: true,
+            ..Default::default()
+        };
         let input = [
             Token::LeftBrace,
             Token::String("key".into()),
@@ -261,9 +651,8 @@ mod tests {
         ];
         let mut map: HashMap<String, Value> = HashMap::new();
         map.insert("key".into(), Value::String("value".into()));
-        let expected = Value::Object(map);
 
-        check(&input, expected);
+        check_with_options(&input, &options, Ok(Value::Object(map)));
     }
 
     #[test]
@@ -276,12 +665,12 @@ mod tests {
             Token::Comma,
             Token::String("key1".into()),
             Token::Colon,
-            Token::Number(16.0),
+            Token::Number(Number::I64(16)),
             Token::RightBrace,
         ];
         let mut map: HashMap<String, Value> = HashMap::new();
         map.insert("key".into(), Value::String("value".into()));
-        map.insert("key1".into(), Value::Number(16.0));
+        map.insert("key1".into(), Value::Number(Number::I64(16)));
         let expected = Value::Object(map);
 
         check(&input, expected);
@@ -330,4 +719,82 @@ mod tests {
 
         check(&input, expected);
     }
+
+    #[test]
+    fn canonical_keys_accepted() {
+        let options = ParseOptions {
+            require_sorted_unique_keys: true,
+            ..Default::default()
+        };
+        let mut map: HashMap<String, Value> = HashMap::new();
+        map.insert("a".into(), Value::Null);
+        map.insert("b".into(), Value::Null);
+
+        check_with_options(
+            &object_with_keys(&["a", "b"]),
+            &options,
+            Ok(Value::Object(map)),
+        );
+    }
+
+    #[test]
+    fn unsorted_keys_rejected() {
+        let options = ParseOptions {
+            require_sorted_unique_keys: true,
+            ..Default::default()
+        };
+        let expected = Err(TokenParseError::KeysNotCanonical("a".into()));
+
+        check_with_options(&object_with_keys(&["b", "a"]), &options, expected);
+    }
+
+    #[test]
+    fn duplicate_keys_not_canonical() {
+        let options = ParseOptions {
+            require_sorted_unique_keys: true,
+            ..Default::default()
+        };
+        let expected = Err(TokenParseError::KeysNotCanonical("a".into()));
+
+        check_with_options(&object_with_keys(&["a", "a"]), &options, expected);
+    }
+
+    #[test]
+    fn unsorted_keys_allowed_by_default() {
+        let mut map: HashMap<String, Value> = HashMap::new();
+        map.insert("a".into(), Value::Null);
+        map.insert("b".into(), Value::Null);
+
+        check(&object_with_keys(&["b", "a"]), Value::Object(map));
+    }
+
+    #[test]
+    fn duplicate_keys_keep_last_value_by_default() {
+        let input = [
+            Token::LeftBrace,
+            Token::String("a".into()),
+            Token::Colon,
+            Token::Number(Number::I64(1)),
+            Token::Comma,
+            Token::String("a".into()),
+            Token::Colon,
+            Token::Number(Number::I64(2)),
+            Token::RightBrace,
+        ];
+        let mut map: HashMap<String, Value> = HashMap::new();
+        map.insert("a".into(), Value::Number(Number::I64(2)));
+
+        check(&input, Value::Object(map));
+    }
+
+    #[test]
+    fn duplicate_keys_rejected_when_strict() {
+        let options = ParseOptions {
+            reject_duplicate_keys: true,
+            ..Default::default()
+        };
+        let expected = Err(TokenParseError::DuplicateKey("a".into()));
+
+        check_with_options(&object_with_keys(&["a", "b", "a"]), &options, expected);
+    }
 }
@@ -0,0 +1,363 @@
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// One of the possible errors that could occur while writing through a JSON
+/// Pointer
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum PointerError {
+    /// The pointer is not empty and does not start with `/`, or contains an
+    /// invalid `~` escape
+    InvalidPointer,
+    /// The value at this pointer is a scalar, so it cannot have children
+    NotAContainer(String),
+    /// The array at this pointer has no element at the requested index
+    IndexOutOfBounds(String),
+}
+
+/// One of the possible errors that could occur in [`Value::set`] and
+/// [`Value::set_existing`]
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SetError {
+    PointerError(PointerError),
+    /// The object at this pointer has no such member, and
+    /// [`Value::set_existing`] does not create intermediate members
+    MissingMember(String),
+}
+
+impl From<PointerError> for SetError {
+    fn from(e: PointerError) -> Self {
+        Self::PointerError(e)
+    }
+}
+
+impl Value {
+    /// Looks up a value by JSON Pointer (RFC 6901), e.g. `"/basic_info/title"`
+    ///
+    /// The empty pointer refers to the value itself. Returns `None` if any
+    /// segment is missing, is not a valid array index, or contains an invalid
+    /// `~` escape.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, token| match value {
+                Value::Object(map) => map.get(&unescape_token(token)?),
+                Value::Array(array) => array.get(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Mutable counterpart of [`Value::pointer`]
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        if pointer.is_empty() {
+            return Some(self);
+        }
+        pointer
+            .strip_prefix('/')?
+            .split('/')
+            .try_fold(self, |value, token| match value {
+                Value::Object(map) => map.get_mut(&unescape_token(token)?),
+                Value::Array(array) => array.get_mut(parse_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// Walks a JSON Pointer like `mkdir -p`, creating missing object members,
+    /// and returns the value at its end
+    ///
+    /// Missing members are inserted as `null`, and `null` values along the way
+    /// are replaced by empty objects. Arrays are only walked into, never
+    /// extended, and any other scalar along the way is an error. The whole
+    /// pointer is checked before anything is created, so an invalid pointer
+    /// leaves `self` unchanged.
+    pub fn ensure_path_mut(&mut self, pointer: &str) -> Result<&mut Value, PointerError> {
+        match self.walk_mut(pointer, true) {
+            Ok(value) => Ok(value),
+            Err(SetError::PointerError(e)) => Err(e),
+            Err(SetError::MissingMember(_)) => unreachable!("missing members are created"),
+        }
+    }
+
+    /// Stores `value` at a JSON Pointer, creating missing objects along the way
+    /// as [`Value::ensure_path_mut`] does
+    ///
+    /// Fails without changing anything if the pointer is invalid, or a scalar
+    /// or a too short array is in the way.
+    pub fn set(&mut self, pointer: &str, value: Value) -> Result<(), SetError> {
+        *self.walk_mut(pointer, true)? = value;
+        Ok(())
+    }
+
+    /// Like [`Value::set`], but only adds the last member of the pointer;
+    /// every value before it must already exist
+    pub fn set_existing(&mut self, pointer: &str, value: Value) -> Result<(), SetError> {
+        *self.walk_mut(pointer, false)? = value;
+        Ok(())
+    }
+
+    /// 沿着 `pointer` 向下走；末尾缺少的成员总会插入，中间缺少的成员只有
+    /// `create_missing` 为真时才插入
+    fn walk_mut(&mut self, pointer: &str, create_missing: bool) -> Result<&mut Value, SetError> {
+        if pointer.is_empty() {
+            return Ok(self);
+        }
+        // 先反转义所有的段，避免走到一半才发现指针无效，留下创建了一半的路径
+        let keys = pointer
+            .strip_prefix('/')
+            .and_then(|rest| {
+                rest.split('/')
+                    .map(|token| Some((token.len(), unescape_token(token)?)))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .ok_or(PointerError::InvalidPointer)?;
+
+        let last = keys.len() - 1;
+        let mut current = self;
+        // pointer[..end] 指向 current
+        let mut end = 0;
+        for (i, (len, key)) in keys.into_iter().enumerate() {
+            if create_missing && matches!(current, Value::Null) {
+                *current = Value::Object(HashMap::new());
+            }
+            current = match current {
+                Value::Object(map) => {
+                    if create_missing || i == last {
+                        map.entry(key).or_insert(Value::Null)
+                    } else {
+                        let missing = || SetError::MissingMember(pointer[..end + len + 1].into());
+                        map.get_mut(&key).ok_or_else(missing)?
+                    }
+                }
+                Value::Array(array) => parse_index(&key)
+                    .and_then(|index| array.get_mut(index))
+                    .ok_or_else(|| PointerError::IndexOutOfBounds(pointer[..end].into()))?,
+                _ => return Err(PointerError::NotAContainer(pointer[..end].into()).into()),
+            };
+            end += len + 1;
+        }
+        Ok(current)
+    }
+
+    /// Returns the value at the first of `pointers` that exists and is not
+    /// `null`, for "use A, else B, else C" style fallbacks
+    pub fn coalesce(&self, pointers: &[&str]) -> Option<&Value> {
+        pointers
+            .iter()
+            .filter_map(|pointer| self.pointer(pointer))
+            .find(|value| !matches!(value, Value::Null))
+    }
+}
+
+/// Escapes `~` and `/` in an object key so it can be used as a JSON Pointer segment
+pub(crate) fn escape_token(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Turns a JSON Pointer segment back into the object key it names
+fn unescape_token(token: &str) -> Option<String> {
+    let mut key = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '~' {
+            match chars.next()? {
+                '0' => key.push('~'),
+                '1' => key.push('/'),
+                _ => return None,
+            }
+        } else {
+            key.push(ch);
+        }
+    }
+    Some(key)
+}
+
+/// Array indices are plain decimal numbers without leading zeros
+fn parse_index(token: &str) -> Option<usize> {
+    let is_digits = !token.is_empty() && token.bytes().all(|b| b.is_ascii_digit());
+    if !is_digits || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PointerError, SetError};
+    use crate::number::Number;
+    use crate::value::{object, Value};
+    use std::collections::HashMap;
+
+    #[test]
+    fn empty_pointer_is_root() {
+        let value = Value::Null;
+        assert_eq!(value.pointer(""), Some(&Value::Null));
+    }
+
+    #[test]
+    fn pointer_requires_leading_slash() {
+        let value = object(vec![("a", Value::Null)]);
+        assert_eq!(value.pointer("a"), None);
+    }
+
+    #[test]
+    fn pointer_unescapes_tokens() {
+        let value = object(vec![
+            ("a/b", Value::Number(Number::F64(1.0))),
+            ("m~n", Value::Number(Number::F64(2.0))),
+        ]);
+        assert_eq!(
+            value.pointer("/a~1b"),
+            Some(&Value::Number(Number::F64(1.0)))
+        );
+        assert_eq!(
+            value.pointer("/m~0n"),
+            Some(&Value::Number(Number::F64(2.0)))
+        );
+        assert_eq!(value.pointer("/m~2n"), None);
+    }
+
+    #[test]
+    fn pointer_rejects_malformed_indices() {
+        let value = Value::Array(vec![Value::Null, Value::Boolean(true)]);
+        assert_eq!(value.pointer("/1"), Some(&Value::Boolean(true)));
+        assert_eq!(value.pointer("/01"), None);
+        assert_eq!(value.pointer("/-"), None);
+        assert_eq!(value.pointer("/+1"), None);
+    }
+
+    #[test]
+    fn pointer_mut_replaces_nested_value() {
+        let mut value = object(vec![("a", Value::Array(vec![Value::Null]))]);
+        *value.pointer_mut("/a/0").unwrap() = Value::Boolean(false);
+        assert_eq!(value.pointer("/a/0"), Some(&Value::Boolean(false)));
+    }
+
+    #[test]
+    fn coalesce_skips_missing_and_null() {
+        let value = object(vec![
+            ("primary", Value::Null),
+            ("fallback", Value::String("used".into())),
+            ("last", Value::String("unused".into())),
+        ]);
+        let actual = value.coalesce(&["/missing", "/primary", "/fallback", "/last"]);
+        assert_eq!(actual, Some(&Value::String("used".into())));
+    }
+
+    #[test]
+    fn coalesce_without_match() {
+        let value = Value::Object(HashMap::new());
+        assert_eq!(value.coalesce(&["/a", "/b"]), None);
+    }
+
+    #[test]
+    fn ensure_path_creates_intermediate_objects() {
+        let mut value = Value::Object(HashMap::new());
+        let leaf = value.ensure_path_mut("/a/b/c").unwrap();
+        assert_eq!(*leaf, Value::Null);
+        *leaf = Value::Number(Number::F64(1.0));
+
+        let expected = object(vec![(
+            "a",
+            object(vec![(
+                "b",
+                object(vec![("c", Value::Number(Number::F64(1.0)))]),
+            )]),
+        )]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn ensure_path_keeps_existing_values() {
+        let mut value = object(vec![("a", Value::Array(vec![object(vec![])]))]);
+        *value.ensure_path_mut("/a/0/b").unwrap() = Value::Boolean(true);
+
+        assert_eq!(value.pointer("/a/0/b"), Some(&Value::Boolean(true)));
+    }
+
+    #[test]
+    fn ensure_path_blocked_by_scalar() {
+        let mut value = object(vec![("a", Value::Number(Number::F64(1.0)))]);
+        assert_eq!(
+            value.ensure_path_mut("/a/b/c"),
+            Err(PointerError::NotAContainer("/a".into()))
+        );
+    }
+
+    #[test]
+    fn ensure_path_does_not_extend_arrays() {
+        let mut value = object(vec![("a", Value::Array(vec![]))]);
+        assert_eq!(
+            value.ensure_path_mut("/a/0"),
+            Err(PointerError::IndexOutOfBounds("/a".into()))
+        );
+        assert_eq!(
+            value.ensure_path_mut("a"),
+            Err(PointerError::InvalidPointer)
+        );
+    }
+
+    #[test]
+    fn set_creates_and_overwrites() {
+        let mut value = object(vec![("a", object(vec![("b", Value::Null)]))]);
+        assert_eq!(value.set("/a/b", Value::Number(Number::F64(1.0))), Ok(()));
+        assert_eq!(value.set("/a/c/d", Value::Boolean(true)), Ok(()));
+
+        let expected = object(vec![(
+            "a",
+            object(vec![
+                ("b", Value::Number(Number::F64(1.0))),
+                ("c", object(vec![("d", Value::Boolean(true))])),
+            ]),
+        )]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn set_blocked_by_scalar() {
+        let mut value = object(vec![("a", Value::String("leaf".into()))]);
+        assert_eq!(
+            value.set("/a/b", Value::Null),
+            Err(SetError::PointerError(PointerError::NotAContainer(
+                "/a".into()
+            )))
+        );
+        assert_eq!(value.pointer("/a"), Some(&Value::String("leaf".into())));
+    }
+
+    #[test]
+    fn invalid_pointer_changes_nothing() {
+        let mut value = object(vec![("a", Value::Null)]);
+        assert_eq!(
+            value.set("/new/x~2", Value::Boolean(true)),
+            Err(SetError::PointerError(PointerError::InvalidPointer))
+        );
+        assert_eq!(
+            value.ensure_path_mut("/a/b/~"),
+            Err(PointerError::InvalidPointer)
+        );
+        assert_eq!(value, object(vec![("a", Value::Null)]));
+    }
+
+    #[test]
+    fn set_existing_needs_every_intermediate() {
+        let mut value = object(vec![("a", object(vec![]))]);
+        assert_eq!(value.set_existing("/a/b", Value::Boolean(true)), Ok(()));
+        assert_eq!(
+            value.set_existing("/a/c/d", Value::Null),
+            Err(SetError::MissingMember("/a/c".into()))
+        );
+        assert_eq!(
+            value.set_existing("/a/b/c", Value::Null),
+            Err(SetError::PointerError(PointerError::NotAContainer(
+                "/a/b".into()
+            )))
+        );
+        assert_eq!(
+            value,
+            object(vec![("a", object(vec![("b", Value::Boolean(true))]))])
+        );
+    }
+}
@@ -0,0 +1,153 @@
+use crate::parse::TokenParseError;
+use crate::tokenize::Span;
+use crate::value::Value;
+use crate::{parse, ParseError};
+
+/// JSON 允许的空白字符
+const WHITESPACE: [char; 4] = [' ', '\t', '\n', '\r'];
+
+/// Parses a top-level array on a best-effort basis: an element that fails to
+/// parse is skipped up to the next `,` or `]`, and parsing continues
+///
+/// Returns the array of the elements that parsed, together with the byte
+/// range of every skipped element (without surrounding whitespace), e.g.
+/// `[1, bad, 3]` gives `[1, 3]` and one span covering `bad`. Elements are
+/// split at commas outside strings and nested containers, so a corrupt
+/// element can still swallow its neighbors if it leaves a bracket or quote
+/// open. Input that is not an array is parsed as with [`parse`].
+pub fn parse_array_recovering(input: &str) -> Result<(Value, Vec<Span>), ParseError> {
+    let start = input.len() - input.trim_start_matches(WHITESPACE).len();
+    if !input[start..].starts_with('[') {
+        return parse(input).map(|value| (value, Vec::new()));
+    }
+
+    let mut elements = Vec::new();
+    let mut skipped = Vec::new();
+    let mut index = start + 1;
+    loop {
+        let (end, is_last) = element_end(input, index).ok_or(ParseError::TokenParseError(
+            TokenParseError::UnexpectedEndOfInput,
+        ))?;
+        let element = &input[index..end];
+        let trimmed = element.trim_matches(WHITESPACE);
+        // `[]` 没有元素，不算跳过
+        let is_empty_array =
+            is_last && trimmed.is_empty() && elements.is_empty() && skipped.is_empty();
+        if !is_empty_array {
+            match parse(element) {
+                Ok(value) => elements.push(value),
+                Err(_) => {
+                    let start =
+                        index + element.len() - element.trim_start_matches(WHITESPACE).len();
+                    skipped.push(Span {
+                        start,
+                        end: start + trimmed.len(),
+                    });
+                }
+            }
+        }
+        index = end + 1;
+        if is_last {
+            break;
+        }
+    }
+
+    if !input[index..].trim_matches(WHITESPACE).is_empty() {
+        return Err(ParseError::TokenParseError(TokenParseError::TrailingTokens));
+    }
+    Ok((Value::Array(elements), skipped))
+}
+
+/// 从 `start` 开始找到当前元素的结尾，即最外层的 `,` 或 `]`，并返回它是否是 `]`
+fn element_end(input: &str, start: usize) -> Option<(usize, bool)> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut is_escaping = false;
+
+    for (offset, &byte) in input.as_bytes()[start..].iter().enumerate() {
+        if in_string {
+            if is_escaping {
+                is_escaping = false;
+            } else if byte == b'\\' {
+                is_escaping = true;
+            } else if byte == b'"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' if depth == 0 => return Some((start + offset, true)),
+            b',' if depth == 0 => return Some((start + offset, false)),
+            b']' | b'}' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_array_recovering;
+    use crate::number::Number;
+    use crate::parse::TokenParseError;
+    use crate::tokenize::Span;
+    use crate::value::Value;
+    use crate::ParseError;
+
+    #[test]
+    fn skips_malformed_element() {
+        let input = "[1, bad, 3]";
+        let (value, skipped) = parse_array_recovering(input).unwrap();
+        assert_eq!(
+            value,
+            Value::Array(vec![
+                Value::Number(Number::I64(1)),
+                Value::Number(Number::I64(3))
+            ])
+        );
+        assert_eq!(skipped, [Span { start: 4, end: 7 }]);
+        assert_eq!(&input[4..7], "bad");
+    }
+
+    #[test]
+    fn keeps_nested_elements_whole() {
+        let input = r#"[[1, 2], {"a": "x,]"}, tru, , "ok"]"#;
+        let (value, skipped) = parse_array_recovering(input).unwrap();
+        assert_eq!(value.as_array().unwrap().len(), 3);
+        assert_eq!(value[1]["a"], "x,]");
+        assert_eq!(value[2], "ok");
+        let skipped: Vec<_> = skipped
+            .iter()
+            .map(|span| &input[span.start..span.end])
+            .collect();
+        assert_eq!(skipped, ["tru", ""]);
+    }
+
+    #[test]
+    fn valid_input_has_no_skips() {
+        assert_eq!(
+            parse_array_recovering(" [] "),
+            Ok((Value::Array(vec![]), vec![]))
+        );
+        assert_eq!(
+            parse_array_recovering(r#"{"a": 1}"#).unwrap().1,
+            Vec::<Span>::new()
+        );
+    }
+
+    #[test]
+    fn unclosed_array_is_error() {
+        assert_eq!(
+            parse_array_recovering("[1, 2"),
+            Err(ParseError::TokenParseError(
+                TokenParseError::UnexpectedEndOfInput
+            ))
+        );
+        assert_eq!(
+            parse_array_recovering("[1] 2"),
+            Err(ParseError::TokenParseError(TokenParseError::TrailingTokens))
+        );
+    }
+}
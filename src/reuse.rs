@@ -0,0 +1,94 @@
+use crate::options::ParseOptions;
+use crate::parse::{parse_document_into, ValueTree};
+use crate::tokenize::{tokenize_reusing, Token};
+use crate::value::Value;
+use crate::ParseError;
+
+/// Parses many documents in a row, reusing its token buffer and the string
+/// buffer used for unescaping between calls instead of allocating them for
+/// every document
+///
+/// Useful in hot loops over many small documents; the buffers grow to fit the
+/// largest document and the longest escaped string parsed so far.
+#[derive(Debug, Default)]
+pub struct Parser {
+    options: ParseOptions,
+    tokens: Vec<Token>,
+    tree: ValueTree,
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_options(options: ParseOptions) -> Self {
+        Self {
+            options,
+            ..Self::default()
+        }
+    }
+
+    /// Parses `input` like [`crate::parse_with_options`] with this parser's
+    /// options
+    pub fn parse_reuse(&mut self, input: &str) -> Result<Value, ParseError> {
+        tokenize_reusing(input, &self.options, &mut self.tokens)?;
+        let value = parse_document_into(&mut self.tokens, &self.options, &mut self.tree)?;
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Parser;
+    use crate::number::Number;
+    use crate::options::ParseOptions;
+    use crate::tokenize::{Position, TokenizeError};
+    use crate::value::Value;
+    use crate::ParseError;
+
+    #[test]
+    fn parses_documents_in_a_row() {
+        let mut parser = Parser::new();
+        assert_eq!(
+            parser.parse_reuse("[1, [2]]"),
+            Ok(Value::Array(vec![
+                Value::Number(Number::I64(1)),
+                Value::Array(vec![Value::Number(Number::I64(2))])
+            ]))
+        );
+        // 较短的输入不能读到上一次残留的 token
+        assert_eq!(parser.parse_reuse("null"), Ok(Value::Null));
+        assert_eq!(
+            parser.parse_reuse("\"unclosed"),
+            Err(ParseError::TokenizeError(
+                TokenizeError::UnclosedQuotes,
+                Position { line: 1, column: 1 }
+            ))
+        );
+        assert_eq!(parser.parse_reuse("true"), Ok(Value::Boolean(true)));
+    }
+
+    #[test]
+    fn unescapes_strings_across_documents() {
+        let mut parser = Parser::new();
+        assert_eq!(
+            parser.parse_reuse(r#" "a long string with \"escapes\"" "#),
+            Ok(Value::String(r#"a long string with "escapes""#.into()))
+        );
+        // 上一次反转义留在缓冲区里的内容不能混进来
+        let value = parser.parse_reuse(r#"{"k\u00e9y": "\n"}"#).unwrap();
+        assert_eq!(value["k\u{e9}y"], "\n");
+    }
+
+    #[test]
+    fn applies_its_options() {
+        let options = ParseOptions {
+            allow_non_finite_literals: true,
+            ..Default::default()
+        };
+        let mut parser = Parser::with_options(options);
+        assert!(parser.parse_reuse("[NaN]").is_ok());
+        assert!(Parser::new().parse_reuse("[NaN]").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Round-trip property tests: random `Value` trees must parse back to
+//! themselves after serialization.
+
+use crate::{parse, Number, SerializeOptions, Value};
+use std::collections::HashMap;
+
+/// Small xorshift generator so the tests stay deterministic and dependency-free
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+}
+
+/// 字符池覆盖需要转义的字符、控制字符和多字节字符
+const CHARS: &[char] = &[
+    'a', 'Z', '0', ' ', '"', '\\', '/', '\n', '\r', '\t', '\u{8}', '\u{c}', '\u{0}', '\u{1f}',
+    '\u{7f}', 'é', '中', '\u{2028}', '😀', '<', '&',
+];
+
+fn random_string(rng: &mut Rng) -> String {
+    let len = rng.below(8);
+    (0..len)
+        .map(|_| CHARS[rng.below(CHARS.len() as u64) as usize])
+        .collect()
+}
+
+/// 整数边界值，超出 2^53 后 `f64` 无法精确表示
+const INTEGERS: &[Number] = &[
+    Number::I64(i64::MIN),
+    Number::I64(i64::MIN + 1),
+    Number::I64(-(1 << 53) - 1),
+    Number::I64(-1),
+    Number::I64(0),
+    Number::I64((1 << 53) + 1),
+    Number::I64(i64::MAX),
+    Number::U64(i64::MAX as u64 + 1),
+    Number::U64(u64::MAX - 1),
+    Number::U64(u64::MAX),
+];
+
+fn random_number(rng: &mut Rng) -> Number {
+    match rng.below(6) {
+        0 => Number::I64(rng.below(1_000) as i64 - 500),
+        1 => Number::I64(rng.next() as i64),
+        2 => Number::from(rng.next()),
+        3 => INTEGERS[rng.below(INTEGERS.len() as u64) as usize],
+        4 => Number::F64(rng.below(1 << 20) as f64 / 64.0),
+        _ => loop {
+            let number = f64::from_bits(rng.next());
+            if number.is_finite() {
+                break Number::F64(number);
+            }
+        },
+    }
+}
+
+fn random_value(rng: &mut Rng, depth: u32) -> Value {
+    let kinds = if depth == 0 { 4 } else { 6 };
+    match rng.below(kinds) {
+        0 => Value::Null,
+        1 => Value::Boolean(rng.below(2) == 0),
+        2 => Value::String(random_string(rng)),
+        3 => Value::Number(random_number(rng)),
+        4 => {
+            let len = rng.below(5);
+            Value::Array((0..len).map(|_| random_value(rng, depth - 1)).collect())
+        }
+        _ => {
+            let len = rng.below(5);
+            let map: HashMap<String, Value> = (0..len)
+                .map(|_| (random_string(rng), random_value(rng, depth - 1)))
+                .collect();
+            Value::Object(map)
+        }
+    }
+}
+
+fn assert_round_trips(value: Value, options: &SerializeOptions) {
+    let json = value.to_string_with_options(options).unwrap();
+    assert_eq!(parse(&json).ok(), Some(value), "{json}");
+}
+
+#[test]
+fn random_values_round_trip() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..2_000 {
+        assert_round_trips(random_value(&mut rng, 4), &SerializeOptions::default());
+    }
+}
+
+#[test]
+fn random_values_round_trip_with_escaping_options() {
+    let options = SerializeOptions {
+        escape_forward_slash: true,
+        html_safe: true,
+        spaced: true,
+        ..Default::default()
+    };
+    let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+    for _ in 0..2_000 {
+        assert_round_trips(random_value(&mut rng, 4), &options);
+    }
+}
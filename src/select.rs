@@ -0,0 +1,102 @@
+use crate::value::Value;
+
+/// One step of a path accepted by [`Value::select`]
+#[derive(Debug, PartialEq)]
+enum Segment<'a> {
+    /// `.key`
+    Key(&'a str),
+    /// `[0]`
+    Index(usize),
+    /// `[*]`
+    Wildcard,
+}
+
+impl Value {
+    /// Selects values with a small subset of JSONPath: `$` for the root,
+    /// `.key` for object members, `[index]` for array elements and `[*]` for
+    /// every element of an array (or every value of an object)
+    ///
+    /// For example `"$.list[*].name"` returns the `name` of every element of
+    /// `list`. A malformed path selects nothing.
+    pub fn select(&self, path: &str) -> Vec<&Value> {
+        let Some(segments) = parse_path(path) else {
+            return Vec::new();
+        };
+
+        let mut current = vec![self];
+        for segment in segments {
+            let mut next = Vec::new();
+            for value in current {
+                match (&segment, value) {
+                    (Segment::Key(key), Value::Object(map)) => next.extend(map.get(*key)),
+                    (Segment::Index(i), Value::Array(array)) => next.extend(array.get(*i)),
+                    (Segment::Wildcard, Value::Array(array)) => next.extend(array),
+                    (Segment::Wildcard, Value::Object(map)) => next.extend(map.values()),
+                    _ => {}
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+fn parse_path(path: &str) -> Option<Vec<Segment<'_>>> {
+    let mut rest = path.strip_prefix('$')?;
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            if end == 0 {
+                return None;
+            }
+            segments.push(Segment::Key(&after_dot[..end]));
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']')?;
+            let segment = match &after_bracket[..end] {
+                "*" => Segment::Wildcard,
+                index => Segment::Index(index.parse().ok()?),
+            };
+            segments.push(segment);
+            rest = &after_bracket[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(segments)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_path, Segment};
+    use crate::value::Value;
+
+    #[test]
+    fn parses_all_segment_kinds() {
+        let expected = vec![
+            Segment::Key("a"),
+            Segment::Index(0),
+            Segment::Wildcard,
+            Segment::Key("b"),
+        ];
+        assert_eq!(parse_path("$.a[0][*].b"), Some(expected));
+        assert_eq!(parse_path("$"), Some(vec![]));
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        assert_eq!(parse_path("a.b"), None);
+        assert_eq!(parse_path("$..a"), None);
+        assert_eq!(parse_path("$[x]"), None);
+        assert_eq!(parse_path("$[0"), None);
+    }
+
+    #[test]
+    fn select_root() {
+        let value = Value::Null;
+        assert_eq!(value.select("$"), vec![&Value::Null]);
+        assert!(value.select("$.a").is_empty());
+    }
+}
@@ -0,0 +1,632 @@
+use crate::value::Value;
+use std::collections::hash_map;
+use std::fmt::{self, Write};
+use std::slice;
+use std::vec;
+
+/// Settings for turning a `Value` back into JSON text
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SerializeOptions {
+    /// Write `/` as `\/`, so that a string such as `"</script>"` cannot end an
+    /// HTML `<script>` element the JSON is embedded in
+    pub escape_forward_slash: bool,
+    /// Write `<`, `>`, `&`, U+2028 and U+2029 as `\u` escapes, so the output
+    /// can be interpolated into HTML or a JavaScript source without being
+    /// interpreted as markup or a line break
+    pub html_safe: bool,
+    /// What to do with NaN and infinite numbers, which JSON cannot represent
+    pub non_finite_policy: NonFinitePolicy,
+    /// Put a space after every `:` and `,`, e.g. `{"a": 1, "b": [1, 2]}`
+    pub spaced: bool,
+    /// Put every array element and object member on its own line, indented
+    /// by this many spaces per nesting level; empty arrays and objects stay
+    /// `[]` and `{}`
+    pub indent: Option<usize>,
+    /// What ends each line when `indent` is set
+    pub line_ending: LineEnding,
+    /// Write object members in key order, so that equal values always
+    /// serialize to the same text
+    pub sort_keys: bool,
+}
+
+/// Line terminator used by the pretty serializer
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    /// `\n`, as on Unix
+    #[default]
+    Lf,
+    /// `\r\n`, for tools targeting Windows editors
+    CrLf,
+}
+
+/// How the serializer handles numbers that have no JSON representation
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NonFinitePolicy {
+    /// Write `null` in their place, like JavaScript's `JSON.stringify`
+    #[default]
+    Null,
+    /// Fail with [`SerializeError::NonFiniteNumber`]
+    Error,
+}
+
+/// One of the possible errors that could occur while serializing a `Value`
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum SerializeError {
+    /// A number was NaN or infinite and the policy was to reject it
+    NonFiniteNumber,
+}
+
+impl Value {
+    /// Serializes the value as compact JSON
+    pub fn to_string_with_options(
+        &self,
+        options: &SerializeOptions,
+    ) -> Result<String, SerializeError> {
+        let mut output = String::new();
+        write_value(&mut output, self, options)?;
+        Ok(output)
+    }
+
+    /// Serializes the value on a single line with a space after every `:` and
+    /// `,`, e.g. `{"a": 1, "b": [1, 2]}`
+    pub fn to_string_spaced(&self) -> String {
+        let options = SerializeOptions {
+            spaced: true,
+            ..Default::default()
+        };
+        self.to_string_with_options(&options)
+            .expect("the default non-finite policy never fails")
+    }
+
+    /// Serializes the value over multiple lines, nesting each level by
+    /// `indent` spaces, e.g. for config files meant to be read by people
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        let options = SerializeOptions {
+            indent: Some(indent),
+            ..Default::default()
+        };
+        self.to_string_with_options(&options)
+            .expect("the default non-finite policy never fails")
+    }
+
+    /// Renders a scalar as plain text for interpolating into templates:
+    /// strings as-is without quotes, numbers as in JSON, booleans as
+    /// `true`/`false`, and `null` as `null_text`
+    ///
+    /// Returns `None` for arrays and objects, and for `null` if `null_text` is
+    /// `None`.
+    pub fn to_scalar_string(&self, null_text: Option<&str>) -> Option<String> {
+        match self {
+            Value::Null => null_text.map(String::from),
+            Value::Boolean(boolean) => Some(boolean.to_string()),
+            Value::Number(number) => Some(number.to_string()),
+            Value::String(string) => Some(string.clone()),
+            Value::Array(_) | Value::Object(_) => None,
+        }
+    }
+
+    /// Encodes an object of scalars as a URL query string such as
+    /// `a=1&b=x%20y`
+    ///
+    /// Pairs are sorted by key, keys and values are percent-encoded, and
+    /// `null` encodes as an empty value. Returns `None` for non-objects and for
+    /// objects containing arrays or objects.
+    pub fn to_query_string(&self) -> Option<String> {
+        let Value::Object(map) = self else {
+            return None;
+        };
+        let mut entries: Vec<_> = map.iter().collect();
+        entries.sort_by_key(|(key, _)| *key);
+
+        let mut output = String::new();
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            if i > 0 {
+                output.push('&');
+            }
+            percent_encode(&mut output, key);
+            output.push('=');
+            match value {
+                Value::Null => {}
+                Value::Boolean(boolean) => output.push_str(&boolean.to_string()),
+                Value::Number(number) => output.push_str(&number.to_string()),
+                Value::String(string) => percent_encode(&mut output, string),
+                Value::Array(_) | Value::Object(_) => return None,
+            }
+        }
+        Some(output)
+    }
+
+    /// A hex digest of the key-sorted compact serialization, e.g. for cache
+    /// keys; equal values give equal checksums whatever their key order
+    ///
+    /// The hash is 64-bit FNV-1a: stable across runs and platforms, but not
+    /// cryptographic, so it must not be trusted against deliberate collisions.
+    pub fn checksum(&self) -> String {
+        let options = SerializeOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        let canonical = self
+            .to_string_with_options(&options)
+            .expect("the default non-finite policy never fails");
+        let hash = canonical
+            .bytes()
+            .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+            });
+        format!("{hash:016x}")
+    }
+}
+
+/// Work left to do while serializing; an explicit stack of these replaces
+/// recursion, so arbitrarily deep trees cannot overflow the call stack
+enum Frame<'a> {
+    Value(&'a Value),
+    /// Remaining elements of an array, and whether none were written yet
+    Array(slice::Iter<'a, Value>, bool),
+    /// Remaining entries of an object, and whether none were written yet
+    Object(Entries<'a>, bool),
+}
+
+/// 对象的成员，按 `sort_keys` 决定是否排序
+enum Entries<'a> {
+    Unordered(hash_map::Iter<'a, String, Value>),
+    Sorted(vec::IntoIter<(&'a String, &'a Value)>),
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = (&'a String, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Entries::Unordered(entries) => entries.next(),
+            Entries::Sorted(entries) => entries.next(),
+        }
+    }
+}
+
+fn write_value(
+    output: &mut String,
+    value: &Value,
+    options: &SerializeOptions,
+) -> Result<(), SerializeError> {
+    let mut stack = vec![Frame::Value(value)];
+    // 当前所在容器的嵌套层数，用于缩进
+    let mut depth = 0;
+
+    while let Some(frame) = stack.pop() {
+        match frame {
+            Frame::Value(value) => match value {
+                Value::Null => output.push_str("null"),
+                Value::Boolean(true) => output.push_str("true"),
+                Value::Boolean(false) => output.push_str("false"),
+                Value::Number(number) if number.is_finite() => {
+                    let _ = write!(output, "{number}");
+                }
+                Value::Number(_) => match options.non_finite_policy {
+                    NonFinitePolicy::Null => output.push_str("null"),
+                    NonFinitePolicy::Error => return Err(SerializeError::NonFiniteNumber),
+                },
+                Value::String(string) => write_string(output, string, options),
+                Value::Array(array) => {
+                    output.push('[');
+                    depth += 1;
+                    stack.push(Frame::Array(array.iter(), true));
+                }
+                Value::Object(map) => {
+                    output.push('{');
+                    depth += 1;
+                    let entries = if options.sort_keys {
+                        let mut sorted: Vec<_> = map.iter().collect();
+                        sorted.sort_unstable_by_key(|(key, _)| *key);
+                        Entries::Sorted(sorted.into_iter())
+                    } else {
+                        Entries::Unordered(map.iter())
+                    };
+                    stack.push(Frame::Object(entries, true));
+                }
+            },
+            Frame::Array(mut items, is_first) => match items.next() {
+                Some(item) => {
+                    if !is_first {
+                        write_separator(output, ',', options);
+                    }
+                    write_line_break(output, depth, options);
+                    stack.push(Frame::Array(items, false));
+                    stack.push(Frame::Value(item));
+                }
+                None => {
+                    depth -= 1;
+                    if !is_first {
+                        write_line_break(output, depth, options);
+                    }
+                    output.push(']');
+                }
+            },
+            Frame::Object(mut entries, is_first) => match entries.next() {
+                Some((key, item)) => {
+                    if !is_first {
+                        write_separator(output, ',', options);
+                    }
+                    write_line_break(output, depth, options);
+                    write_string(output, key, options);
+                    write_separator(output, ':', options);
+                    stack.push(Frame::Object(entries, false));
+                    stack.push(Frame::Value(item));
+                }
+                None => {
+                    depth -= 1;
+                    if !is_first {
+                        write_line_break(output, depth, options);
+                    }
+                    output.push('}');
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+fn write_separator(output: &mut String, separator: char, options: &SerializeOptions) {
+    output.push(separator);
+    // 缩进模式下逗号后面紧跟换行，不需要空格
+    let pretty_colon = options.indent.is_some() && separator == ':';
+    if pretty_colon || (options.spaced && options.indent.is_none()) {
+        output.push(' ');
+    }
+}
+
+/// Starts a new line at the given nesting level if indentation is enabled
+fn write_line_break(output: &mut String, depth: usize, options: &SerializeOptions) {
+    if let Some(indent) = options.indent {
+        output.push_str(match options.line_ending {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        });
+        output.extend(std::iter::repeat_n(' ', indent * depth));
+    }
+}
+
+fn write_string(output: &mut String, string: &str, options: &SerializeOptions) {
+    output.push('"');
+    for ch in string.chars() {
+        match ch {
+            '"' => output.push_str(r#"\""#),
+            '\\' => output.push_str(r"\\"),
+            '\n' => output.push_str(r"\n"),
+            '\r' => output.push_str(r"\r"),
+            '\t' => output.push_str(r"\t"),
+            '\u{8}' => output.push_str(r"\b"),
+            '\u{c}' => output.push_str(r"\f"),
+            '/' if options.escape_forward_slash => output.push_str(r"\/"),
+            '<' if options.html_safe => output.push_str(r"\u003C"),
+            '>' if options.html_safe => output.push_str(r"\u003E"),
+            '&' if options.html_safe => output.push_str(r"\u0026"),
+            '\u{2028}' if options.html_safe => output.push_str(r"\u2028"),
+            '\u{2029}' if options.html_safe => output.push_str(r"\u2029"),
+            // 其余控制字符只能用 \u 转义
+            c if (c as u32) < 0x20 => {
+                let _ = write!(output, "\\u{:04x}", c as u32);
+            }
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Writes the value as compact JSON, so `value.to_string()` gives e.g.
+/// `{"a":1,"b":[1,2]}`; NaN and infinite numbers are written as `null`
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let json = self
+            .to_string_with_options(&SerializeOptions::default())
+            .expect("the default non-finite policy never fails");
+        f.write_str(&json)
+    }
+}
+
+/// Percent-encodes everything except the RFC 3986 unreserved characters
+fn percent_encode(output: &mut String, input: &str) {
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                output.push(byte as char)
+            }
+            _ => {
+                let _ = write!(output, "%{byte:02X}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineEnding, NonFinitePolicy, SerializeError, SerializeOptions};
+    use crate::number::Number;
+    use crate::value::Value;
+    use std::collections::HashMap;
+
+    fn check(value: Value, expected: &str) {
+        let actual = value
+            .to_string_with_options(&SerializeOptions::default())
+            .unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn serializes_literals() {
+        check(Value::Null, "null");
+        check(Value::Boolean(true), "true");
+        check(Value::Boolean(false), "false");
+    }
+
+    #[test]
+    fn serializes_numbers() {
+        check(Value::Number(Number::F64(16.0)), "16");
+        check(Value::Number(Number::F64(1.25)), "1.25");
+    }
+
+    #[test]
+    fn display_round_trips() {
+        let mut map = HashMap::new();
+        map.insert(
+            "a \"b\"".into(),
+            Value::Array(vec![Value::Null, Value::Number(Number::F64(1.5))]),
+        );
+        map.insert("c".into(), Value::Boolean(false));
+        let value = Value::Array(vec![Value::Object(map), Value::String("\t".into())]);
+
+        assert_eq!(crate::parse(&value.to_string()), Ok(value));
+        assert_eq!(Value::Number(Number::F64(f64::NAN)).to_string(), "null");
+    }
+
+    #[test]
+    fn pretty_prints_nested_values() {
+        let mut inner = HashMap::new();
+        inner.insert(
+            "a".into(),
+            Value::Array(vec![Value::Number(Number::F64(1.0)), Value::Array(vec![])]),
+        );
+        let value = Value::Array(vec![Value::Object(inner), Value::Object(HashMap::new())]);
+
+        let expected = "[\n  {\n    \"a\": [\n      1,\n      []\n    ]\n  },\n  {}\n]";
+        assert_eq!(value.to_pretty_string(2), expected);
+        assert_eq!(
+            Value::Array(vec![Value::Null]).to_pretty_string(0),
+            "[\nnull\n]"
+        );
+    }
+
+    #[test]
+    fn pretty_prints_with_chosen_line_ending() {
+        let value = crate::parse(r#"{"a": [1, []]}"#).unwrap();
+        let crlf = SerializeOptions {
+            indent: Some(2),
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        };
+        assert_eq!(
+            value.to_string_with_options(&crlf).unwrap(),
+            "{\r\n  \"a\": [\r\n    1,\r\n    []\r\n  ]\r\n}"
+        );
+        assert_eq!(
+            value.to_pretty_string(2),
+            "{\n  \"a\": [\n    1,\n    []\n  ]\n}"
+        );
+
+        // 不缩进时没有换行
+        let compact = SerializeOptions {
+            line_ending: LineEnding::CrLf,
+            ..Default::default()
+        };
+        assert_eq!(
+            value.to_string_with_options(&compact).unwrap(),
+            r#"{"a":[1,[]]}"#
+        );
+    }
+
+    #[test]
+    fn sorts_keys_when_asked() {
+        let value =
+            crate::parse(r#"{"b": {"d": 1, "c": 2}, "a": [{"z": null, "y": true}]}"#).unwrap();
+        let options = SerializeOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            value.to_string_with_options(&options).unwrap(),
+            r#"{"a":[{"y":true,"z":null}],"b":{"c":2,"d":1}}"#
+        );
+    }
+
+    #[test]
+    fn checksum_ignores_key_order() {
+        let value = crate::parse(r#"{"a": 1, "b": [true, {"x": "1", "y": null}]}"#).unwrap();
+        let reordered = crate::parse(r#"{"b": [true, {"y": null, "x": "1"}], "a": 1.0}"#).unwrap();
+        let changed = crate::parse(r#"{"a": 1, "b": [true, {"x": 1, "y": null}]}"#).unwrap();
+
+        assert_eq!(value.checksum(), reordered.checksum());
+        assert_ne!(value.checksum(), changed.checksum());
+        assert_eq!(value.checksum().len(), 16);
+        // 摘要与平台无关，可以写死
+        assert_eq!(Value::Null.checksum(), "5b9bc4ba528108e4");
+    }
+
+    #[test]
+    fn renders_scalars_as_text() {
+        assert_eq!(
+            Value::String("a \"b\"".into()).to_scalar_string(None),
+            Some(String::from("a \"b\""))
+        );
+        assert_eq!(
+            Value::Number(Number::F64(16.0)).to_scalar_string(None),
+            Some(String::from("16"))
+        );
+        assert_eq!(
+            Value::Number(Number::F64(-1.5)).to_scalar_string(None),
+            Some(String::from("-1.5"))
+        );
+        assert_eq!(
+            Value::Boolean(true).to_scalar_string(None),
+            Some(String::from("true"))
+        );
+        assert_eq!(
+            Value::Null.to_scalar_string(Some("null")),
+            Some(String::from("null"))
+        );
+        assert_eq!(Value::Null.to_scalar_string(None), None);
+        assert_eq!(Value::Array(vec![]).to_scalar_string(Some("")), None);
+    }
+
+    #[test]
+    fn serializes_string_escapes() {
+        let value = Value::String("\"\\\n\r\t\u{8}\u{c}\u{1}".into());
+        check(value, r#""\"\\\n\r\t\b\f\u0001""#);
+    }
+
+    #[test]
+    fn serializes_containers() {
+        let mut map = HashMap::new();
+        map.insert(
+            "key".into(),
+            Value::Array(vec![Value::Null, Value::Number(Number::F64(1.0))]),
+        );
+        check(Value::Object(map), r#"{"key":[null,1]}"#);
+        check(Value::Array(vec![]), "[]");
+        check(Value::Object(HashMap::new()), "{}");
+    }
+
+    #[test]
+    fn serializes_deeply_nested_array() {
+        const DEPTH: usize = 100_000;
+        let mut value = Value::Array(vec![]);
+        for _ in 1..DEPTH {
+            value = Value::Array(vec![value]);
+        }
+
+        let actual = value
+            .to_string_with_options(&SerializeOptions::default())
+            .unwrap();
+        assert_eq!(actual, "[".repeat(DEPTH) + &"]".repeat(DEPTH));
+
+        // 逐层拆开，避免递归 drop 导致栈溢出
+        while let Value::Array(mut array) = value {
+            value = array.pop().unwrap_or(Value::Null);
+        }
+    }
+
+    #[test]
+    fn forward_slash_plain_by_default() {
+        check(Value::String("</script>".into()), r#""</script>""#);
+    }
+
+    #[test]
+    fn forward_slash_escaped_with_option() {
+        let options = SerializeOptions {
+            escape_forward_slash: true,
+            ..Default::default()
+        };
+        let actual = Value::String("</script>".into())
+            .to_string_with_options(&options)
+            .unwrap();
+        assert_eq!(actual, r#""<\/script>""#);
+    }
+
+    #[test]
+    fn html_characters_plain_by_default() {
+        check(
+            Value::String("<script>a && b</script>".into()),
+            r#""<script>a && b</script>""#,
+        );
+    }
+
+    #[test]
+    fn html_safe_escapes_markup() {
+        let options = SerializeOptions {
+            html_safe: true,
+            ..Default::default()
+        };
+        let value = Value::String("<script>a && b</script>\u{2028}\u{2029}".into());
+        let actual = value.to_string_with_options(&options).unwrap();
+        assert_eq!(
+            actual,
+            r#""\u003Cscript\u003Ea \u0026\u0026 b\u003C/script\u003E\u2028\u2029""#
+        );
+    }
+
+    #[test]
+    fn spaced_object() {
+        let mut map = HashMap::new();
+        map.insert("a".into(), Value::Number(Number::F64(1.0)));
+        map.insert("b".into(), Value::Number(Number::F64(2.0)));
+
+        let actual = Value::Object(map).to_string_spaced();
+        assert!(
+            actual == r#"{"a": 1, "b": 2}"# || actual == r#"{"b": 2, "a": 1}"#,
+            "{actual}"
+        );
+    }
+
+    #[test]
+    fn spaced_array() {
+        let value = Value::Array(vec![Value::Number(Number::F64(1.0)), Value::Array(vec![])]);
+        assert_eq!(value.to_string_spaced(), "[1, []]");
+    }
+
+    #[test]
+    fn non_finite_numbers_become_null_by_default() {
+        check(
+            Value::Array(vec![
+                Value::Number(Number::F64(f64::NAN)),
+                Value::Number(Number::F64(f64::INFINITY)),
+            ]),
+            "[null,null]",
+        );
+    }
+
+    #[test]
+    fn non_finite_numbers_rejected_with_error_policy() {
+        let options = SerializeOptions {
+            non_finite_policy: NonFinitePolicy::Error,
+            ..Default::default()
+        };
+        let value = Value::Array(vec![Value::Number(Number::F64(f64::NAN))]);
+        assert_eq!(
+            value.to_string_with_options(&options),
+            Err(SerializeError::NonFiniteNumber)
+        );
+    }
+
+    #[test]
+    fn query_string_from_flat_object() {
+        let mut map = HashMap::new();
+        map.insert("b".into(), Value::String("x y".into()));
+        map.insert("a".into(), Value::Number(Number::F64(1.0)));
+        let value = Value::Object(map);
+
+        assert_eq!(value.to_query_string(), Some("a=1&b=x%20y".into()));
+    }
+
+    #[test]
+    fn query_string_encodes_non_ascii_and_null() {
+        let mut map = HashMap::new();
+        map.insert("城市".into(), Value::String("a&b=c".into()));
+        map.insert("flag".into(), Value::Boolean(true));
+        map.insert("none".into(), Value::Null);
+        let value = Value::Object(map);
+
+        assert_eq!(
+            value.to_query_string(),
+            Some("flag=true&none=&%E5%9F%8E%E5%B8%82=a%26b%3Dc".into())
+        );
+    }
+
+    #[test]
+    fn query_string_rejects_nested_values() {
+        let mut map = HashMap::new();
+        map.insert("a".into(), Value::Array(vec![]));
+
+        assert_eq!(Value::Object(map).to_query_string(), None);
+        assert_eq!(Value::Number(Number::F64(1.0)).to_query_string(), None);
+    }
+}
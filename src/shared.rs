@@ -0,0 +1,204 @@
+use crate::number::Number;
+use crate::options::ParseOptions;
+use crate::parse::{parse_document_into, unescape_chars, TokenParseError, Tree};
+use crate::tokenize::tokenize_located;
+use crate::value::Value;
+use crate::ParseError;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+/// A JSON value in which identical strings, keys and string values alike,
+/// share one allocation
+///
+/// Returned by [`parse_shared`]. Saves memory on documents that repeat the
+/// same strings many times, such as arrays of records with a few distinct
+/// labels.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SharedValue {
+    Null,
+    Boolean(bool),
+    String(Arc<str>),
+    Number(Number),
+    Array(Vec<SharedValue>),
+    Object(HashMap<Arc<str>, SharedValue>),
+}
+
+impl SharedValue {
+    /// Borrows the string of a `String` value
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SharedValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// The value of member `key` if this is an object
+    pub fn get(&self, key: &str) -> Option<&SharedValue> {
+        match self {
+            SharedValue::Object(map) => map.get(key),
+            _ => None,
+        }
+    }
+}
+
+/// Parses like [`crate::parse`], but interns every string while parsing, so
+/// that equal strings point to the same `Arc<str>`
+pub fn parse_shared(input: &str) -> Result<SharedValue, ParseError> {
+    parse_shared_with_options(input, &ParseOptions::default())
+}
+
+/// Parses like [`crate::parse_with_options`], interning strings as with
+/// [`parse_shared`]
+pub fn parse_shared_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<SharedValue, ParseError> {
+    let mut tokens = tokenize_located(input, options)?;
+    let value = parse_document_into(&mut tokens, options, &mut SharedTree::default())?;
+    Ok(value)
+}
+
+/// 解析时把字符串放进集合，已经出现过的字符串直接共用
+#[derive(Debug, Default)]
+struct SharedTree {
+    strings: HashSet<Arc<str>>,
+    /// 反转义用的缓冲区，重复的字符串因此不需要分配
+    scratch: String,
+}
+
+impl SharedTree {
+    fn intern(&mut self, raw: String) -> Result<Arc<str>, TokenParseError> {
+        let string = if raw.contains('\\') {
+            self.scratch.clear();
+            unescape_chars(raw.chars(), '"', |ch| self.scratch.push(ch))?;
+            self.scratch.as_str()
+        } else {
+            &raw
+        };
+        if let Some(shared) = self.strings.get(string) {
+            return Ok(Arc::clone(shared));
+        }
+        let shared: Arc<str> = Arc::from(string);
+        self.strings.insert(Arc::clone(&shared));
+        Ok(shared)
+    }
+}
+
+impl Tree for SharedTree {
+    type Value = SharedValue;
+    type Key = Arc<str>;
+    type Object = HashMap<Arc<str>, SharedValue>;
+
+    fn scalar(&mut self, scalar: Value) -> SharedValue {
+        match scalar {
+            Value::Boolean(boolean) => SharedValue::Boolean(boolean),
+            Value::Number(number) => SharedValue::Number(number),
+            _ => SharedValue::Null,
+        }
+    }
+
+    fn string(&mut self, raw: String) -> Result<SharedValue, TokenParseError> {
+        self.intern(raw).map(SharedValue::String)
+    }
+
+    fn array(&mut self, array: Vec<SharedValue>) -> SharedValue {
+        SharedValue::Array(array)
+    }
+
+    fn key(&mut self, raw: String) -> Result<Arc<str>, TokenParseError> {
+        self.intern(raw)
+    }
+
+    fn key_str(key: &Arc<str>) -> &str {
+        key
+    }
+
+    fn contains_key(object: &Self::Object, key: &Arc<str>) -> bool {
+        object.contains_key(key)
+    }
+
+    fn is_empty(object: &Self::Object) -> bool {
+        object.is_empty()
+    }
+
+    fn insert(object: &mut Self::Object, key: Arc<str>, value: SharedValue) {
+        object.insert(key, value);
+    }
+
+    fn object(&mut self, object: Self::Object) -> SharedValue {
+        SharedValue::Object(object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_shared, parse_shared_with_options, SharedValue};
+    use crate::number::Number;
+    use crate::options::ParseOptions;
+    use crate::parse::TokenParseError;
+    use crate::ParseError;
+    use std::sync::Arc;
+
+    #[test]
+    fn shares_repeated_strings() {
+        let value = parse_shared(r#"[{"state": "on"}, {"state": "on"}, "on"]"#).unwrap();
+        let SharedValue::Array(items) = &value else {
+            panic!("expected an array");
+        };
+        let (SharedValue::String(a), SharedValue::String(b), SharedValue::String(c)) = (
+            items[0].get("state").unwrap(),
+            items[1].get("state").unwrap(),
+            &items[2],
+        ) else {
+            panic!("expected strings");
+        };
+        assert!(Arc::ptr_eq(a, b));
+        assert!(Arc::ptr_eq(a, c));
+
+        let (SharedValue::Object(first), SharedValue::Object(second)) = (&items[0], &items[1])
+        else {
+            panic!("expected objects");
+        };
+        let key = |map: &std::collections::HashMap<Arc<str>, SharedValue>| {
+            Arc::clone(map.get_key_value("state").unwrap().0)
+        };
+        assert!(Arc::ptr_eq(&key(first), &key(second)));
+    }
+
+    #[test]
+    fn shares_strings_after_unescaping() {
+        let value = parse_shared(r#"["a\u0062", "ab"]"#).unwrap();
+        let SharedValue::Array(items) = &value else {
+            panic!("expected an array");
+        };
+        let (SharedValue::String(a), SharedValue::String(b)) = (&items[0], &items[1]) else {
+            panic!("expected strings");
+        };
+        assert_eq!(&**a, "ab");
+        assert!(Arc::ptr_eq(a, b));
+    }
+
+    #[test]
+    fn keeps_other_values() {
+        let value = parse_shared(r#"{"n": 1, "b": true, "z": null, "s": "x"}"#).unwrap();
+        assert_eq!(value.get("n"), Some(&SharedValue::Number(Number::I64(1))));
+        assert_eq!(value.get("b"), Some(&SharedValue::Boolean(true)));
+        assert_eq!(value.get("z"), Some(&SharedValue::Null));
+        assert_eq!(value.get("s").and_then(SharedValue::as_str), Some("x"));
+        assert_eq!(value.get("missing"), None);
+    }
+
+    #[test]
+    fn applies_parse_options() {
+        let options = ParseOptions {
+            require_sorted_unique_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_shared_with_options(r#"{"b": 1, "a": 2}"#, &options),
+            Err(ParseError::TokenParseError(
+                TokenParseError::KeysNotCanonical("a".into())
+            ))
+        );
+    }
+}
@@ -1,5 +1,11 @@
+use crate::number::Number;
+use crate::options::{ControlCharPolicy, ParseOptions};
+use crate::parse::unescape_chars;
+use crate::ErrorKind;
+use std::borrow::Cow;
+use std::fmt;
 use std::num::ParseFloatError;
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     /// `{`
     LeftBrace,
@@ -19,10 +25,13 @@ pub enum Token {
     False,
     /// `true`
     True,
-    /// Any number literal
-    Number(f64),
+    /// Any number literal, kept as an integer if it has no fraction or exponent
+    Number(Number),
     /// Key of the key/value pair or string value
     String(String),
+    /// A run of whitespace between tokens, only produced with
+    /// `ParseOptions::keep_whitespace`
+    Whitespace(String),
 }
 
 #[cfg(test)]
@@ -43,38 +52,317 @@ pub enum TokenizeError {
     UnclosedQuotes,
     /// Character is not part of a JSON token
     CharNotRecognized(char),
+    /// A control character (U+0000 to U+001F) appeared unescaped inside a string
+    UnescapedControlCharacter(char),
+    /// The input started with a U+FEFF byte order mark and the options reject it
+    ByteOrderMark,
+    /// A string contained a malformed escape sequence; only reported by the
+    /// validating tokenizer behind `is_valid`, as the parser checks escapes
+    /// otherwise
+    InvalidEscape,
+    /// The input produced more tokens than `ParseOptions::max_tokens` allows
+    TooManyTokens,
+    /// A number had a leading zero followed by more digits, such as `012`
+    InvalidLeadingZero,
+    /// A number had no digit after its sign or decimal point, such as `-.5`
+    /// or `1.`
+    MissingDigits,
+    /// A `/*` comment was never closed
+    UnclosedComment,
 }
 
+impl TokenizeError {
+    /// The coarse category of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            TokenizeError::UnclosedQuotes | TokenizeError::UnclosedComment => {
+                ErrorKind::UnexpectedEof
+            }
+            TokenizeError::ByteOrderMark => ErrorKind::Encoding,
+            TokenizeError::TooManyTokens => ErrorKind::LimitExceeded,
+            _ => ErrorKind::Syntax,
+        }
+    }
+}
+
+/// Line and column of the start of the token that failed to tokenize, both
+/// counted from 1, with columns counted in characters
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// 位置紧跟在 `before` 之后
+    fn of(before: &str) -> Self {
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Position {
+            line: before.bytes().filter(|&byte| byte == b'\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// Byte range of a token within the input, `start` inclusive and `end` exclusive
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Splits the input into JSON tokens, e.g. for syntax highlighting or linting
+///
+/// ```
+/// use json_parser::{tokenize, Number, Token};
+///
+/// let tokens = tokenize("[1,2]").unwrap();
+/// assert_eq!(
+///     tokens,
+///     [
+///         Token::LeftBracket,
+///         Token::Number(Number::I64(1)),
+///         Token::Comma,
+///         Token::Number(Number::I64(2)),
+///         Token::RightBracket,
+///     ]
+/// );
+/// ```
 pub fn tokenize(input: &str) -> Result<Vec<Token>, TokenizeError> {
-    let chars: Vec<_> = input.chars().collect();
+    tokenize_with_options(input, &ParseOptions::default())
+}
+
+pub fn tokenize_with_options(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<Token>, TokenizeError> {
+    tokenize_located(input, options).map_err(|(e, _)| e)
+}
+
+/// Like [`tokenize_with_options`], but also reports where the error occurred,
+/// e.g. `line 312, column 8` when printed
+pub fn tokenize_located(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<Token>, (TokenizeError, Position)> {
+    let tokens = scan(input, options)?;
+    Ok(tokens.into_iter().map(|(token, _)| token).collect())
+}
+
+/// Tokenizes the input, pairing each token with the byte range it came from
+pub fn tokenize_with_spans(input: &str) -> Result<Vec<(Token, Span)>, TokenizeError> {
+    scan(input, &ParseOptions::default()).map_err(|(e, _)| e)
+}
+
+/// Tokenizes as far as possible, returning every token before the first
+/// error together with that error
+///
+/// This lets recovery tools work with the valid prefix of a broken document.
+pub fn tokenize_lenient(input: &str) -> (Vec<Token>, Option<TokenizeError>) {
+    let mut tokens = Vec::with_capacity(input.len() / 4);
+    let error = scan_into(input, &ParseOptions::default(), true, &mut tokens)
+        .err()
+        .map(|(e, _)| e);
+    (tokens.into_iter().map(|(token, _)| token).collect(), error)
+}
+
+fn scan(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<(Token, Span)>, (TokenizeError, Position)> {
+    // JSON 平均每个 token 约占 4 个字节，预先分配以减少扩容
+    let mut tokens = Vec::with_capacity(input.len() / 4);
+    scan_into(input, options, true, &mut tokens)?;
+    Ok(tokens)
+}
+
+/// Tokenizes for validation only: strings are checked, including their
+/// escapes, but every `Token::String` is left empty so nothing is copied
+pub(crate) fn tokenize_validating(
+    input: &str,
+    options: &ParseOptions,
+) -> Result<Vec<Token>, TokenizeError> {
+    let mut tokens = Vec::with_capacity(input.len() / 4);
+    scan_into(input, options, false, &mut tokens).map_err(|(e, _)| e)?;
+    Ok(tokens.into_iter().map(|(token, _)| token).collect())
+}
+
+/// `materialize_strings` 为 false 时只校验字符串，不保存其内容
+fn scan_into(
+    input: &str,
+    options: &ParseOptions,
+    materialize_strings: bool,
+    tokens: &mut Vec<(Token, Span)>,
+) -> Result<(), (TokenizeError, Position)> {
+    scan_str(input, options, materialize_strings, &mut |token, span| {
+        tokens.push((token, span))
+    })
+}
+
+/// Tokenizes the input into the given buffer, which is cleared first, so
+/// that repeated calls reuse its allocation
+pub(crate) fn tokenize_reusing(
+    input: &str,
+    options: &ParseOptions,
+    tokens: &mut Vec<Token>,
+) -> Result<(), (TokenizeError, Position)> {
+    tokens.clear();
+    scan_str(input, options, true, &mut |token, _| tokens.push(token))
+}
+
+/// 直接按字节下标扫描输入，不把整个输入收集成 `Vec<char>`
+fn scan_str(
+    input: &str,
+    options: &ParseOptions,
+    materialize_strings: bool,
+    emit: &mut impl FnMut(Token, Span),
+) -> Result<(), (TokenizeError, Position)> {
+    // 当前字符在输入中的字节偏移
     let mut index = 0;
+    let mut count = 0;
 
-    let mut tokens = Vec::new();
-    while index < chars.len() {
-        if !chars[index].is_whitespace() {
-            let token = make_token(&chars, &mut index)?;
-            tokens.push(token);
+    if input.starts_with('\u{feff}') {
+        if options.reject_bom {
+            return Err((
+                TokenizeError::ByteOrderMark,
+                Position { line: 1, column: 1 },
+            ));
         }
-        index += 1;
+        index = '\u{feff}'.len_utf8();
     }
 
-    Ok(tokens)
+    while let Some(ch) = char_at(input, index) {
+        if options.allow_comments && ch == '/' {
+            let start = index;
+            if skip_comment(input, &mut index).map_err(|e| (e, Position::of(&input[..start])))? {
+                continue;
+            }
+        }
+        if is_whitespace(ch, options) && !options.keep_whitespace {
+            index += ch.len_utf8();
+            continue;
+        }
+        let start = index;
+        count += 1;
+        if options.max_tokens.is_some_and(|max| count > max) {
+            return Err((TokenizeError::TooManyTokens, Position::of(&input[..start])));
+        }
+        let token = if is_whitespace(ch, options) {
+            // 把连续的空白合并成一个 token
+            while let Some(ch) = char_at(input, index).filter(|&ch| is_whitespace(ch, options)) {
+                index += ch.len_utf8();
+            }
+            Token::Whitespace(input[start..index].to_owned())
+        } else {
+            make_token(input, &mut index, options, materialize_strings)
+                .map_err(|e| (e, Position::of(&input[..start])))?
+        };
+        emit(token, Span { start, end: index });
+    }
+
+    Ok(())
+}
+
+/// 跳过 `index` 处的 `//` 或 `/* */` 注释，没有注释时返回 `false`
+///
+/// 字符串由 `tokenize_string` 整体读取，所以字符串里的 `//` 不会走到这里。
+fn skip_comment(input: &str, index: &mut usize) -> Result<bool, TokenizeError> {
+    let rest = &input[*index..];
+    if rest.starts_with("//") {
+        *index += rest.find('\n').map_or(rest.len(), |end| end + 1);
+    } else if let Some(body) = rest.strip_prefix("/*") {
+        let end = body.find("*/").ok_or(TokenizeError::UnclosedComment)?;
+        *index += "/*".len() + end + "*/".len();
+    } else {
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// 从字节偏移 `index` 开始的字符
+fn char_at(input: &str, index: usize) -> Option<char> {
+    input[index..].chars().next()
+}
+
+/// Finds the token covering the given byte offset, e.g. the token under an
+/// editor's cursor
+///
+/// Returns `None` if the offset falls on whitespace or outside the input, or
+/// if the input fails to tokenize.
+pub fn token_at(input: &str, offset: usize) -> Option<(Token, Span)> {
+    tokenize_with_spans(input)
+        .ok()?
+        .into_iter()
+        .find(|(_, span)| span.start <= offset && offset < span.end)
 }
 
-fn make_token(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
-    let ch = chars[*index];
+/// JSON only allows space, tab, line feed and carriage return between tokens
+fn is_whitespace(ch: char, options: &ParseOptions) -> bool {
+    if options.unicode_whitespace {
+        ch.is_whitespace()
+    } else {
+        matches!(ch, ' ' | '\t' | '\n' | '\r')
+    }
+}
+
+/// 读取从 `index` 开始的一个 token，并把 `index` 移到它之后
+fn make_token(
+    input: &str,
+    index: &mut usize,
+    options: &ParseOptions,
+    materialize_strings: bool,
+) -> Result<Token, TokenizeError> {
+    let Some(ch) = char_at(input, *index) else {
+        unreachable!("make_token is only called before the end of the input");
+    };
+    let structural = match ch {
+        '[' => Some(Token::LeftBracket),
+        ']' => Some(Token::RightBracket),
+        '{' => Some(Token::LeftBrace),
+        '}' => Some(Token::RightBrace),
+        ',' => Some(Token::Comma),
+        ':' => Some(Token::Colon),
+        _ => None,
+    };
+    if let Some(token) = structural {
+        *index += 1;
+        return Ok(token);
+    }
+
     let token = match ch {
-        '[' => Token::LeftBracket,
-        ']' => Token::RightBracket,
-        '{' => Token::LeftBrace,
-        '}' => Token::RightBrace,
-        ',' => Token::Comma,
-        ':' => Token::Colon,
-        'n' => tokenize_literal(chars, index, "null", Token::Null)?,
-        't' => tokenize_literal(chars, index, "true", Token::True)?,
-        'f' => tokenize_literal(chars, index, "false", Token::False)?,
-        c if c.is_ascii_digit() => tokenize_float(chars, index)?,
-        '"' => tokenize_string(chars, index)?,
+        'n' => tokenize_literal(input, index, "null", Token::Null)?,
+        't' => tokenize_literal(input, index, "true", Token::True)?,
+        'f' => tokenize_literal(input, index, "false", Token::False)?,
+        'N' if options.allow_non_finite_literals => {
+            tokenize_literal(input, index, "NaN", Token::Number(Number::F64(f64::NAN)))?
+        }
+        'I' if options.allow_non_finite_literals => tokenize_literal(
+            input,
+            index,
+            "Infinity",
+            Token::Number(Number::F64(f64::INFINITY)),
+        )?,
+        '-' if options.allow_non_finite_literals
+            && input.as_bytes().get(*index + 1) == Some(&b'I') =>
+        {
+            tokenize_literal(
+                input,
+                index,
+                "-Infinity",
+                Token::Number(Number::F64(f64::NEG_INFINITY)),
+            )?
+        }
+        c if c.is_ascii_digit() || c == '-' => tokenize_float(input, index, options)?,
+        '"' => tokenize_string(input, index, options, materialize_strings, b'"')?,
+        '\'' if options.allow_single_quotes => {
+            tokenize_string(input, index, options, materialize_strings, b'\'')?
+        }
         ch => return Err(TokenizeError::CharNotRecognized(ch)),
     };
 
@@ -82,70 +370,200 @@ fn make_token(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError>
 }
 
 fn tokenize_literal(
-    chars: &[char],
+    input: &str,
     index: &mut usize,
     literal: &str,
     token: Token,
 ) -> Result<Token, TokenizeError> {
-    for expected_char in literal.chars() {
-        if expected_char != chars[*index] {
-            return Err(TokenizeError::UnfinishedLiteralValue);
-        }
-        *index += 1;
+    if !input[*index..].starts_with(literal) {
+        return Err(TokenizeError::UnfinishedLiteralValue);
     }
-    *index -= 1;
+    *index += literal.len();
     Ok(token)
 }
 
-fn tokenize_float(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
-    let mut unparsed_num = String::new();
+fn tokenize_float(
+    input: &str,
+    index: &mut usize,
+    options: &ParseOptions,
+) -> Result<Token, TokenizeError> {
+    let bytes = input.as_bytes();
+    let start = *index;
     let mut has_decimal = false;
+    let mut has_exponent = false;
+    // 当前这一组整数位的位数，以及是否已经出现过千位分隔符
+    let mut group_len = 0;
+    let mut has_separator = false;
 
-    while *index < chars.len() {
-        let ch = chars[*index];
-        match ch {
-            c if c.is_ascii_digit() => unparsed_num.push(c),
-            c if c == '.' && !has_decimal => {
-                unparsed_num.push(c);
+    if bytes[*index] == b'-' {
+        *index += 1;
+    }
+    // 正负号和小数点后面都至少要有一位数字
+    let digit_at = |index: usize| bytes.get(index).is_some_and(u8::is_ascii_digit);
+    if !digit_at(*index) {
+        return Err(TokenizeError::MissingDigits);
+    }
+    // JSON 的整数部分除了 0 本身不能以 0 开头
+    if bytes[*index] == b'0' && digit_at(*index + 1) {
+        return Err(TokenizeError::InvalidLeadingZero);
+    }
+    while let Some(&byte) = bytes.get(*index) {
+        match byte {
+            b'0'..=b'9' => group_len += 1,
+            b',' if options.allow_thousands_separators
+                && !has_decimal
+                && !has_exponent
+                && is_thousands_separator(bytes, *index, group_len, has_separator) =>
+            {
+                group_len = 0;
+                has_separator = true;
+            }
+            b'.' if !has_decimal && !has_exponent => {
+                if !digit_at(*index + 1) {
+                    return Err(TokenizeError::MissingDigits);
+                }
                 has_decimal = true;
             }
+            b'e' | b'E' if !has_exponent => {
+                has_exponent = true;
+                // 指数可以带一个正负号
+                if let Some(b'+' | b'-') = bytes.get(*index + 1) {
+                    *index += 1;
+                }
+            }
             _ => break,
         }
         *index += 1;
     }
-    // 回退一个字符
-    *index -= 1;
+    let literal = &input[start..*index];
+    // 只有用了千位分隔符时才需要复制一份去掉逗号的数字
+    let unparsed_num = if has_separator {
+        Cow::Owned(literal.replace(',', ""))
+    } else {
+        Cow::Borrowed(literal)
+    };
+    // 没有小数和指数的整数尽量保持精确；`-0` 要保留符号，只能用浮点数
+    if !has_decimal && !has_exponent && unparsed_num != "-0" {
+        if let Ok(int) = unparsed_num.parse::<i64>() {
+            return Ok(Token::Number(Number::I64(int)));
+        }
+        if let Ok(int) = unparsed_num.parse::<u64>() {
+            return Ok(Token::Number(Number::U64(int)));
+        }
+    }
     let num = unparsed_num
         .parse()
-        .map(Token::Number)
+        .map(|float| Token::Number(Number::F64(float)))
         .map_err(TokenizeError::ParseNumberError)?;
     Ok(num)
 }
 
-fn tokenize_string(chars: &[char], index: &mut usize) -> Result<Token, TokenizeError> {
-    let mut string = String::new();
+/// 逗号前的一组有 1 到 3 位（前面已有分隔符时必须是 3 位），后面正好跟 3 位数字
+fn is_thousands_separator(
+    bytes: &[u8],
+    comma: usize,
+    group_len: usize,
+    has_separator: bool,
+) -> bool {
+    let group_ok = if has_separator {
+        group_len == 3
+    } else {
+        (1..=3).contains(&group_len)
+    };
+    let is_digit = |offset: usize| bytes.get(comma + offset).is_some_and(u8::is_ascii_digit);
+    group_ok && (1..=3).all(is_digit) && !is_digit(4)
+}
+
+fn tokenize_string(
+    input: &str,
+    index: &mut usize,
+    options: &ParseOptions,
+    materialize_strings: bool,
+    quote: u8,
+) -> Result<Token, TokenizeError> {
+    // 引号、反斜杠和控制字符都是 ASCII，不会出现在多字节字符的编码中，
+    // 所以可以逐字节扫描
+    let bytes = input.as_bytes();
+    let start = *index + 1;
+    let mut has_stripped = false;
     let mut is_escaping = false;
 
     loop {
         *index += 1;
-        if *index >= chars.len() {
+        let Some(&byte) = bytes.get(*index) else {
             return Err(TokenizeError::UnclosedQuotes);
-        }
-        let ch = chars[*index];
+        };
 
-        match ch {
-            '"' if !is_escaping => break,
-            '\\' => is_escaping = !is_escaping,
+        if byte < 0x20 {
+            match options.control_char_policy {
+                ControlCharPolicy::Error => {
+                    return Err(TokenizeError::UnescapedControlCharacter(char::from(byte)))
+                }
+                // 去掉的字符当作不存在，所以不影响 is_escaping，
+                // 前面的反斜杠转义的是它后面的字符
+                ControlCharPolicy::Strip => {
+                    has_stripped = true;
+                    continue;
+                }
+                ControlCharPolicy::Allow => {}
+            }
+        }
+        match byte {
+            _ if byte == quote && !is_escaping => break,
+            b'\\' => is_escaping = !is_escaping,
             _ => is_escaping = false,
         }
-        string.push(ch);
     }
+    let content = &input[start..*index];
+    // 跳过右引号
+    *index += 1;
+
+    let string = if !materialize_strings {
+        unescape_chars(content.chars(), char::from(quote), |_| {})
+            .map_err(|_| TokenizeError::InvalidEscape)?;
+        String::new()
+    } else if quote == b'\'' {
+        unescape_single_quotes(content, has_stripped)
+    } else if has_stripped {
+        content.chars().filter(|&ch| ch >= '\u{20}').collect()
+    } else {
+        content.to_owned()
+    };
     Ok(Token::String(string))
 }
 
+/// 把单引号字符串中的 `\'` 换成 `'`，其余转义原样留给解析器，
+/// 这样解析器只需认识双引号字符串的转义
+fn unescape_single_quotes(content: &str, strip_control_chars: bool) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut chars = content
+        .chars()
+        .filter(|&ch| !strip_control_chars || ch >= '\u{20}');
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            output.push(ch);
+            continue;
+        }
+        match chars.next() {
+            Some('\'') => output.push('\''),
+            Some(next) => {
+                output.push('\\');
+                output.push(next);
+            }
+            None => output.push('\\'),
+        }
+    }
+    output
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{tokenize, Token, TokenizeError};
+    use super::{
+        token_at, tokenize, tokenize_lenient, tokenize_with_options, tokenize_with_spans, Span,
+        Token, TokenizeError,
+    };
+    use crate::number::Number;
+    use crate::options::{ControlCharPolicy, ParseOptions};
 
     #[test]
     fn just_comma() {
@@ -203,7 +621,7 @@ mod tests {
     #[test]
     fn integer() {
         let input = String::from("123");
-        let expected = [Token::Number(123.0)];
+        let expected = [Token::Number(Number::I64(123))];
 
         let actual = tokenize(&input).unwrap();
 
@@ -212,12 +630,130 @@ mod tests {
     #[test]
     fn floating_point() {
         let input = String::from("1.23");
-        let expected = [Token::Number(1.23)];
+        let expected = [Token::Number(Number::F64(1.23))];
 
         let actual = tokenize(&input).unwrap();
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn negative_numbers() {
+        let input = String::from("[-0, -123, -1.5]");
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual[1], Token::Number(Number::F64(-0.0)));
+        assert_eq!(actual[3], Token::Number(Number::I64(-123)));
+        assert_eq!(actual[5], Token::Number(Number::F64(-1.5)));
+
+        let Token::Number(Number::F64(zero)) = actual[1] else {
+            panic!("expected a number");
+        };
+        assert!(zero.is_sign_negative());
+    }
+
+    #[test]
+    fn lone_minus_is_error() {
+        assert_eq!(tokenize("[-]"), Err(TokenizeError::MissingDigits));
+    }
+
+    #[test]
+    fn sign_and_decimal_point_require_digits() {
+        for input in ["-.5", "1.", "-", "1.e5", "[-x]", "[1.]"] {
+            assert_eq!(
+                tokenize(input),
+                Err(TokenizeError::MissingDigits),
+                "{input}"
+            );
+        }
+        assert_eq!(tokenize("-5").unwrap(), [Token::Number(Number::I64(-5))]);
+        assert_eq!(
+            tokenize("1.5e5").unwrap(),
+            [Token::Number(Number::F64(1.5e5))]
+        );
+    }
+
+    #[test]
+    fn exponent_notation() {
+        let input = String::from("[1e10, 2.5E-3, 0e0, 6.022e+23]");
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual[1], Token::Number(Number::F64(1e10)));
+        assert_eq!(actual[3], Token::Number(Number::F64(2.5e-3)));
+        assert_eq!(actual[5], Token::Number(Number::F64(0.0)));
+        assert_eq!(actual[7], Token::Number(Number::F64(6.022e23)));
+    }
+
+    #[test]
+    fn exponent_requires_digits() {
+        for input in ["1e", "[1e]", "1e+", "1E-,"] {
+            assert!(
+                matches!(tokenize(input), Err(TokenizeError::ParseNumberError(_))),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn thousands_separators_in_locale_mode() {
+        let options = ParseOptions {
+            allow_thousands_separators: true,
+            ..Default::default()
+        };
+        let input = r#"{"a": 1,000.50, "b": -12,345,678, "c": 1234,567, "d": 1,23}"#;
+        let actual = tokenize_with_options(input, &options).unwrap();
+        assert_eq!(actual[3], Token::Number(Number::F64(1000.5)));
+        assert_eq!(actual[7], Token::Number(Number::I64(-12345678)));
+        // 不符合分组规则的逗号仍然是分隔 token 的逗号
+        assert_eq!(actual[11], Token::Number(Number::I64(1234)));
+        assert_eq!(actual[12], Token::Comma);
+        assert_eq!(actual[13], Token::Number(Number::I64(567)));
+        assert_eq!(actual[17], Token::Number(Number::I64(1)));
+        assert_eq!(actual[18], Token::Comma);
+
+        let strict = tokenize("[1,234]").unwrap();
+        assert_eq!(strict.len(), 5);
+    }
+
+    #[test]
+    fn truncated_literals_are_errors() {
+        for input in ["nul", "tru", "fals", "[nul", "tr ue"] {
+            assert_eq!(
+                tokenize(input),
+                Err(TokenizeError::UnfinishedLiteralValue),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn too_many_tokens() {
+        let options = ParseOptions {
+            max_tokens: Some(5),
+            ..Default::default()
+        };
+        assert_eq!(tokenize_with_options("[1, 2]", &options).unwrap().len(), 5);
+        assert_eq!(
+            tokenize_with_options("[1, 2, 3]", &options),
+            Err(TokenizeError::TooManyTokens)
+        );
+    }
+
+    #[test]
+    fn rejects_leading_zeros() {
+        for input in ["01", "00", "012", "-01", "[1, 007]"] {
+            assert_eq!(
+                tokenize(input),
+                Err(TokenizeError::InvalidLeadingZero),
+                "{input}"
+            );
+        }
+        assert_eq!(tokenize("0").unwrap(), [Token::Number(Number::I64(0))]);
+        assert_eq!(tokenize("0.5").unwrap(), [Token::Number(Number::F64(0.5))]);
+        assert_eq!(tokenize("10").unwrap(), [Token::Number(Number::I64(10))]);
+        assert_eq!(
+            tokenize("-0.05").unwrap(),
+            [Token::Number(Number::F64(-0.05))]
+        );
+    }
+
     #[test]
     fn just_ken() {
         let input = String::from("\"ken\"");
@@ -245,5 +781,365 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
-}
 
+    #[test]
+    fn raw_tab_in_string_rejected_by_default() {
+        let input = String::from("\"a\tb\"");
+        let expected = Err(TokenizeError::UnescapedControlCharacter('\t'));
+
+        let actual = tokenize(&input);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn raw_tab_in_string_stripped() {
+        let input = String::from("\"a\tb\"");
+        let options = ParseOptions {
+            control_char_policy: ControlCharPolicy::Strip,
+            ..Default::default()
+        };
+        let expected = [Token::string("ab")];
+
+        let actual = tokenize_with_options(&input, &options).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn backslash_before_stripped_tab_escapes_next_character() {
+        let options = ParseOptions {
+            control_char_policy: ControlCharPolicy::Strip,
+            ..Default::default()
+        };
+        let actual = tokenize_with_options("\"\\\tn\"", &options).unwrap();
+        assert_eq!(actual, [Token::string("\\n")]);
+        let actual = tokenize_with_options("\"\\\t\"\"", &options).unwrap();
+        assert_eq!(actual, [Token::string("\\\"")]);
+    }
+
+    #[test]
+    fn raw_tab_in_string_allowed() {
+        let input = String::from("\"a\tb\"");
+        let options = ParseOptions {
+            control_char_policy: ControlCharPolicy::Allow,
+            ..Default::default()
+        };
+        let expected = [Token::string("a\tb")];
+
+        let actual = tokenize_with_options(&input, &options).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn skips_leading_bom_by_default() {
+        let input = String::from("\u{feff}[]");
+        let expected = vec![
+            (Token::LeftBracket, Span { start: 3, end: 4 }),
+            (Token::RightBracket, Span { start: 4, end: 5 }),
+        ];
+
+        let actual = tokenize_with_spans(&input).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn skips_leading_bom_when_not_rejected() {
+        let input = String::from("\u{feff}null");
+        let options = ParseOptions {
+            reject_bom: false,
+            ..Default::default()
+        };
+
+        let actual = tokenize_with_options(&input, &options).unwrap();
+        assert_eq!(actual, [Token::Null]);
+    }
+
+    #[test]
+    fn rejects_leading_bom_with_option() {
+        let input = String::from("\u{feff}null");
+        let options = ParseOptions {
+            reject_bom: true,
+            ..Default::default()
+        };
+
+        let actual = tokenize_with_options(&input, &options);
+        assert_eq!(actual, Err(TokenizeError::ByteOrderMark));
+    }
+
+    #[test]
+    fn bom_after_start_not_skipped() {
+        let input = String::from("[\u{feff}]");
+        let expected = Err(TokenizeError::CharNotRecognized('\u{feff}'));
+
+        let actual = tokenize(&input);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn non_finite_literals_rejected_by_default() {
+        assert_eq!(tokenize("NaN"), Err(TokenizeError::CharNotRecognized('N')));
+        assert_eq!(
+            tokenize("Infinity"),
+            Err(TokenizeError::CharNotRecognized('I'))
+        );
+        assert_eq!(tokenize("-Infinity"), Err(TokenizeError::MissingDigits));
+    }
+
+    #[test]
+    fn non_finite_literals_with_option() {
+        let input = String::from("[Infinity, -Infinity, Nope]");
+        let options = ParseOptions {
+            allow_non_finite_literals: true,
+            ..Default::default()
+        };
+        let expected = Err(TokenizeError::UnfinishedLiteralValue);
+
+        let actual = tokenize_with_options(&input, &options);
+        assert_eq!(actual, expected);
+
+        let tokens = tokenize_with_options("[Infinity, -Infinity]", &options).unwrap();
+        assert_eq!(tokens[1], Token::Number(Number::F64(f64::INFINITY)));
+        assert_eq!(tokens[3], Token::Number(Number::F64(f64::NEG_INFINITY)));
+    }
+
+    #[test]
+    fn json_whitespace() {
+        let input = String::from(" \t\r\n[ 1 ]\n");
+        let expected = [
+            Token::LeftBracket,
+            Token::Number(Number::I64(1)),
+            Token::RightBracket,
+        ];
+
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn rejects_no_break_space() {
+        let input = String::from("[1,\u{a0}2]");
+        let expected = Err(TokenizeError::CharNotRecognized('\u{a0}'));
+
+        let actual = tokenize(&input);
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn accepts_no_break_space_leniently() {
+        let input = String::from("[1,\u{a0}2]");
+        let options = ParseOptions {
+            unicode_whitespace: true,
+            ..Default::default()
+        };
+        let expected = [
+            Token::LeftBracket,
+            Token::Number(Number::I64(1)),
+            Token::Comma,
+            Token::Number(Number::I64(2)),
+            Token::RightBracket,
+        ];
+
+        let actual = tokenize_with_options(&input, &options).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn more_tokens_than_estimated_capacity() {
+        let input = "[]".repeat(1000);
+        let expected: Vec<_> = (0..1000)
+            .flat_map(|_| [Token::LeftBracket, Token::RightBracket])
+            .collect();
+
+        let actual = tokenize(&input).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn empty_input() {
+        let actual = tokenize("").unwrap();
+        assert_eq!(actual, []);
+    }
+
+    #[test]
+    fn lenient_keeps_tokens_before_error() {
+        let input = String::from("[1, true, @@");
+        let expected = vec![
+            Token::LeftBracket,
+            Token::Number(Number::I64(1)),
+            Token::Comma,
+            Token::True,
+            Token::Comma,
+        ];
+
+        let (tokens, error) = tokenize_lenient(&input);
+        assert_eq!(tokens, expected);
+        assert_eq!(error, Some(TokenizeError::CharNotRecognized('@')));
+    }
+
+    #[test]
+    fn lenient_without_error() {
+        let input = String::from("[1]");
+        let expected = vec![
+            Token::LeftBracket,
+            Token::Number(Number::I64(1)),
+            Token::RightBracket,
+        ];
+
+        let (tokens, error) = tokenize_lenient(&input);
+        assert_eq!(tokens, expected);
+        assert_eq!(error, None);
+    }
+
+    #[test]
+    fn spans_are_byte_offsets() {
+        let input = String::from(r#"["olá", 12]"#);
+        let expected = [
+            (Token::LeftBracket, Span { start: 0, end: 1 }),
+            (Token::string("olá"), Span { start: 1, end: 7 }),
+            (Token::Comma, Span { start: 7, end: 8 }),
+            (Token::Number(Number::I64(12)), Span { start: 9, end: 11 }),
+            (Token::RightBracket, Span { start: 11, end: 12 }),
+        ];
+
+        let actual = tokenize_with_spans(&input).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn large_input_tokenizes_like_its_parts() {
+        let item = r#"{"名字": "olá\n", "n": [-1.5e3, 0, 2199039482869], "ok": true}"#;
+        let item_tokens = tokenize(item).unwrap();
+        let input = format!("[{}]", vec![item; 10_000].join(",\n"));
+
+        let mut expected = vec![Token::LeftBracket];
+        for i in 0..10_000 {
+            if i > 0 {
+                expected.push(Token::Comma);
+            }
+            expected.extend(item_tokens.iter().cloned());
+        }
+        expected.push(Token::RightBracket);
+        let actual = tokenize_with_spans(&input).unwrap();
+        assert_eq!(actual.last().unwrap().1.end, input.len());
+        assert!(actual.into_iter().map(|(token, _)| token).eq(expected));
+    }
+
+    #[test]
+    fn keeps_whitespace_runs_with_option() {
+        let options = ParseOptions {
+            keep_whitespace: true,
+            ..Default::default()
+        };
+        let expected = [
+            Token::LeftBracket,
+            Token::Whitespace(" ".into()),
+            Token::Number(Number::I64(1)),
+            Token::Whitespace(" ".into()),
+            Token::Comma,
+            Token::Number(Number::I64(2)),
+            Token::Whitespace(" \n\t".into()),
+            Token::RightBracket,
+        ];
+        assert_eq!(
+            tokenize_with_options("[ 1 ,2 \n\t]", &options),
+            Ok(expected.to_vec())
+        );
+        assert_eq!(tokenize("[ 1 ,2 ]").unwrap().len(), 5);
+    }
+
+    #[test]
+    fn skips_comments_with_option() {
+        let options = ParseOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        let input = "{\"a\": 1, // first\n/* second,\n   spans lines */ \"b\": \"//\" /**/}// end";
+        let expected = [
+            Token::LeftBrace,
+            Token::string("a"),
+            Token::Colon,
+            Token::Number(Number::I64(1)),
+            Token::Comma,
+            Token::string("b"),
+            Token::Colon,
+            Token::string("//"),
+            Token::RightBrace,
+        ];
+        assert_eq!(
+            tokenize_with_options(input, &options),
+            Ok(expected.to_vec())
+        );
+        assert_eq!(tokenize(input), Err(TokenizeError::CharNotRecognized('/')));
+    }
+
+    #[test]
+    fn rejects_malformed_comments() {
+        let options = ParseOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            tokenize_with_options("[1] /* open", &options),
+            Err(TokenizeError::UnclosedComment)
+        );
+        assert_eq!(
+            tokenize_with_options("[1] / 2", &options),
+            Err(TokenizeError::CharNotRecognized('/'))
+        );
+    }
+
+    #[test]
+    fn single_quoted_strings_with_option() {
+        let options = ParseOptions {
+            allow_single_quotes: true,
+            ..Default::default()
+        };
+        let input = r#"['hello', 'it\'s "quoted"', "mixed 'ok'"]"#;
+        let expected = [
+            Token::LeftBracket,
+            Token::string("hello"),
+            Token::Comma,
+            Token::string(r#"it's "quoted""#),
+            Token::Comma,
+            Token::string("mixed 'ok'"),
+            Token::RightBracket,
+        ];
+        assert_eq!(
+            tokenize_with_options(input, &options),
+            Ok(expected.to_vec())
+        );
+        assert_eq!(
+            tokenize_with_options("'unclosed", &options),
+            Err(TokenizeError::UnclosedQuotes)
+        );
+    }
+
+    #[test]
+    fn single_quotes_rejected_by_default() {
+        assert_eq!(
+            tokenize("['hello']"),
+            Err(TokenizeError::CharNotRecognized('\''))
+        );
+    }
+
+    #[test]
+    fn token_at_inside_string() {
+        let input = String::from(r#"{"key": 123}"#);
+        let expected = Some((Token::string("key"), Span { start: 1, end: 6 }));
+
+        assert_eq!(token_at(&input, 3), expected);
+    }
+
+    #[test]
+    fn token_at_inside_number() {
+        let input = String::from(r#"{"key": 123}"#);
+        let expected = Some((Token::Number(Number::I64(123)), Span { start: 8, end: 11 }));
+
+        assert_eq!(token_at(&input, 9), expected);
+    }
+
+    #[test]
+    fn token_at_whitespace() {
+        let input = String::from(r#"{"key": 123}"#);
+        assert_eq!(token_at(&input, 7), None);
+    }
+}
@@ -0,0 +1,557 @@
+use crate::number::Number;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::mem;
+
+impl Value {
+    /// Replaces every object key in the tree with `f(key)`, e.g. to convert
+    /// between snake_case and camelCase
+    ///
+    /// If two keys of the same object map to the same new key, only the entry
+    /// whose original key sorts first in byte order is kept, so the result
+    /// does not depend on the order of the hash map.
+    pub fn rename_keys(&mut self, f: impl Fn(&str) -> String) {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::Array(array) => stack.extend(array.iter_mut()),
+                Value::Object(map) => {
+                    rekey(map, |key| f(&key));
+                    stack.extend(map.values_mut());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Renames the key `from` of this object to `to`, returning whether
+    /// `from` existed
+    ///
+    /// An existing entry under `to` is overwritten. Objects are unordered, so
+    /// there is no position to preserve. Does nothing for other values.
+    pub fn rename_key(&mut self, from: &str, to: &str) -> bool {
+        let Value::Object(map) = self else {
+            return false;
+        };
+        match map.remove(from) {
+            Some(item) => {
+                map.insert(to.to_owned(), item);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Calls `f` on every scalar in the tree (everything except arrays and
+    /// objects), e.g. to scale all numbers, leaving the structure intact
+    pub fn map_values(&mut self, mut f: impl FnMut(&mut Value)) {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::Array(array) => stack.extend(array.iter_mut().rev()),
+                Value::Object(map) => stack.extend(map.values_mut()),
+                scalar => f(scalar),
+            }
+        }
+    }
+
+    /// Releases excess capacity of every string, array and object in the tree,
+    /// e.g. before caching a document that was built up or pruned
+    ///
+    /// Object keys cannot be modified in place and keep their capacity.
+    pub fn shrink_to_fit(&mut self) {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::String(string) => string.shrink_to_fit(),
+                Value::Array(array) => {
+                    array.shrink_to_fit();
+                    stack.extend(array.iter_mut());
+                }
+                Value::Object(map) => {
+                    map.shrink_to_fit();
+                    stack.extend(map.values_mut());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Makes the value an array, for fields that hold either one item or a
+    /// list of them, and returns its elements
+    ///
+    /// Arrays are left as they are, and any other value, `null` included,
+    /// becomes a one-element array, the same way [`Value::ensure_object`]
+    /// wraps it.
+    pub fn ensure_array(&mut self) -> &mut Vec<Value> {
+        match self {
+            Value::Array(_) => {}
+            _ => {
+                let item = mem::replace(self, Value::Null);
+                *self = Value::Array(vec![item]);
+            }
+        }
+        match self {
+            Value::Array(array) => array,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Makes the value an object and returns its entries
+    ///
+    /// Objects are left as they are, and any other value, `null` included,
+    /// is wrapped as the single member `key`, the same way
+    /// [`Value::ensure_array`] wraps it.
+    pub fn ensure_object(&mut self, key: &str) -> &mut HashMap<String, Value> {
+        match self {
+            Value::Object(_) => {}
+            _ => {
+                let item = mem::replace(self, Value::Null);
+                *self = Value::Object(HashMap::from([(key.to_string(), item)]));
+            }
+        }
+        match self {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Removes repeated elements from every array in the tree, keeping the
+    /// first occurrence of each
+    ///
+    /// Elements are compared by structural equality, which is quadratic in the
+    /// length of each array.
+    pub fn dedup_arrays(&mut self) {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::Array(array) => {
+                    let items = mem::take(array);
+                    for item in items {
+                        if !array.contains(&item) {
+                            array.push(item);
+                        }
+                    }
+                    stack.extend(array.iter_mut());
+                }
+                Value::Object(map) => stack.extend(map.values_mut()),
+                _ => {}
+            }
+        }
+    }
+
+    /// Appends the elements of another array, keeping only the first
+    /// occurrence of every element, e.g. `["a","b"]` and `["b","c"]` become
+    /// `["a","b","c"]`
+    ///
+    /// Elements of any type are compared by structural equality, which is
+    /// quadratic in the length of the result. If either value is not an array,
+    /// nothing changes and `other` is given back.
+    pub fn concat_unique(&mut self, other: Value) -> Result<(), Value> {
+        let Value::Array(array) = self else {
+            return Err(other);
+        };
+        let items = match other {
+            Value::Array(items) => items,
+            other => return Err(other),
+        };
+
+        let existing = mem::take(array);
+        for item in existing.into_iter().chain(items) {
+            if !array.contains(&item) {
+                array.push(item);
+            }
+        }
+        Ok(())
+    }
+
+    /// Trims leading and trailing ASCII whitespace from every string in the
+    /// tree, and from every object key if `trim_keys` is set, e.g. to clean
+    /// up scraped data
+    ///
+    /// As with [`Value::rename_keys`], of the keys that become equal after
+    /// trimming only the entry whose original key sorts first is kept.
+    pub fn trim_strings(&mut self, trim_keys: bool) {
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::String(string) => trim_in_place(string),
+                Value::Array(array) => stack.extend(array.iter_mut()),
+                Value::Object(map) => {
+                    if trim_keys {
+                        rekey(map, |mut key| {
+                            trim_in_place(&mut key);
+                            key
+                        });
+                    }
+                    stack.extend(map.values_mut());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Turns every float in the tree that has an exact integer value, such as
+    /// `16.0`, into an integer `Number`, so the tree matches what parsing the
+    /// serialized output would give
+    ///
+    /// `-0.0` stays a float, as an integer would lose its sign.
+    pub fn prettify_numbers(&mut self) {
+        self.map_values(|value| {
+            if let Value::Number(number) = value {
+                if let Some(int) = integral(*number) {
+                    *number = int;
+                }
+            }
+        });
+    }
+}
+
+/// 整数值的浮点数对应的整数表示
+fn integral(number: Number) -> Option<Number> {
+    let Number::F64(float) = number else {
+        return None;
+    };
+    if float.fract() != 0.0 || (float == 0.0 && float.is_sign_negative()) {
+        return None;
+    }
+    // 2^63 和 2^64 都能被 f64 精确表示
+    if (-(2f64.powi(63))..2f64.powi(63)).contains(&float) {
+        Some(Number::I64(float as i64))
+    } else if (0.0..2f64.powi(64)).contains(&float) {
+        Some(Number::U64(float as u64))
+    } else {
+        None
+    }
+}
+
+/// 把每个键换成 `f(key)`；新键相同时按原来的键排序，保留最小的那个
+fn rekey(map: &mut HashMap<String, Value>, mut f: impl FnMut(String) -> String) {
+    let mut entries: Vec<_> = mem::take(map).into_iter().collect();
+    entries.sort_unstable_by(|(a, _), (b, _)| a.cmp(b));
+    for (key, item) in entries {
+        map.entry(f(key)).or_insert(item);
+    }
+}
+
+fn trim_in_place(string: &mut String) {
+    let trimmed = string.trim_ascii();
+    if trimmed.len() != string.len() {
+        *string = trimmed.to_owned();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::number::Number;
+    use crate::value::{object, Value};
+
+    fn to_camel_case(key: &str) -> String {
+        let mut output = String::new();
+        let mut upper_next = false;
+        for ch in key.chars() {
+            if ch == '_' {
+                upper_next = true;
+            } else if upper_next {
+                output.extend(ch.to_uppercase());
+                upper_next = false;
+            } else {
+                output.push(ch);
+            }
+        }
+        output
+    }
+
+    #[test]
+    fn renames_nested_keys() {
+        let mut value = object(vec![
+            ("basic_info", object(vec![("start_time", Value::Null)])),
+            (
+                "rank_list",
+                Value::Array(vec![object(vec![(
+                    "user_id",
+                    Value::Number(Number::F64(1.0)),
+                )])]),
+            ),
+        ]);
+        value.rename_keys(to_camel_case);
+
+        let expected = object(vec![
+            ("basicInfo", object(vec![("startTime", Value::Null)])),
+            (
+                "rankList",
+                Value::Array(vec![object(vec![(
+                    "userId",
+                    Value::Number(Number::F64(1.0)),
+                )])]),
+            ),
+        ]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn colliding_keys_keep_the_first_original_key() {
+        // "aB" 在字节序上排在 "a_b" 前面
+        let mut value = object(vec![("a_b", Value::Boolean(true)), ("aB", Value::Null)]);
+        value.rename_keys(to_camel_case);
+        assert_eq!(value, object(vec![("aB", Value::Null)]));
+
+        let mut value = object(vec![("aB", Value::Boolean(true)), ("a_b", Value::Null)]);
+        value.rename_keys(to_camel_case);
+        assert_eq!(value, object(vec![("aB", Value::Boolean(true))]));
+    }
+
+    #[test]
+    fn renames_single_key() {
+        let mut value = object(vec![
+            ("rank", Value::Number(Number::I64(3))),
+            ("score", Value::Null),
+        ]);
+        assert!(value.rename_key("rank", "position"));
+        assert_eq!(
+            value,
+            object(vec![
+                ("position", Value::Number(Number::I64(3))),
+                ("score", Value::Null),
+            ])
+        );
+        assert!(!value.rename_key("missing", "other"));
+        assert!(!Value::Null.rename_key("rank", "position"));
+    }
+
+    #[test]
+    fn rename_key_overwrites_existing_target() {
+        let mut value = object(vec![
+            ("old", Value::Boolean(true)),
+            ("new", Value::Boolean(false)),
+        ]);
+        assert!(value.rename_key("old", "new"));
+        assert_eq!(value, object(vec![("new", Value::Boolean(true))]));
+    }
+
+    fn strings(items: &[&str]) -> Value {
+        items
+            .iter()
+            .map(|item| Value::String((*item).into()))
+            .collect()
+    }
+
+    #[test]
+    fn concat_unique_merges_string_arrays() {
+        let mut value = strings(&["a", "b"]);
+        assert_eq!(value.concat_unique(strings(&["b", "c"])), Ok(()));
+        assert_eq!(value, strings(&["a", "b", "c"]));
+    }
+
+    #[test]
+    fn concat_unique_dedups_other_elements() {
+        let mut value = Value::Array(vec![
+            Value::Number(Number::F64(1.0)),
+            Value::Number(Number::F64(1.0)),
+        ]);
+        let other = Value::Array(vec![Value::Null, Value::Number(Number::F64(1.0))]);
+        assert_eq!(value.concat_unique(other), Ok(()));
+        assert_eq!(
+            value,
+            Value::Array(vec![Value::Number(Number::F64(1.0)), Value::Null])
+        );
+    }
+
+    #[test]
+    fn concat_unique_gives_back_non_arrays() {
+        let mut value = strings(&["a"]);
+        assert_eq!(value.concat_unique(Value::Null), Err(Value::Null));
+
+        let mut value = Value::Null;
+        assert_eq!(value.concat_unique(strings(&["a"])), Err(strings(&["a"])));
+    }
+
+    #[test]
+    fn dedup_arrays_by_structure() {
+        let mut value = Value::Array(vec![
+            Value::Number(Number::F64(1.0)),
+            Value::Number(Number::F64(1.0)),
+            Value::Number(Number::F64(2.0)),
+            object(vec![("a", Value::Number(Number::F64(1.0)))]),
+            object(vec![("a", Value::Number(Number::F64(1.0)))]),
+        ]);
+        value.dedup_arrays();
+
+        let expected = Value::Array(vec![
+            Value::Number(Number::F64(1.0)),
+            Value::Number(Number::F64(2.0)),
+            object(vec![("a", Value::Number(Number::F64(1.0)))]),
+        ]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn dedup_nested_arrays() {
+        let mut value = object(vec![(
+            "a",
+            Value::Array(vec![Value::Array(vec![Value::Null, Value::Null])]),
+        )]);
+        value.dedup_arrays();
+
+        let expected = object(vec![(
+            "a",
+            Value::Array(vec![Value::Array(vec![Value::Null])]),
+        )]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn map_values_only_visits_scalars() {
+        let mut value = Value::Array(vec![
+            Value::Number(Number::F64(1.0)),
+            object(vec![(
+                "a",
+                Value::Array(vec![Value::Number(Number::F64(2.0))]),
+            )]),
+            Value::String("x".into()),
+        ]);
+        let mut visited = Vec::new();
+        value.map_values(|leaf| {
+            visited.push(format!("{leaf:?}"));
+            if let Value::Number(number) = leaf {
+                *number = Number::F64(number.as_f64() * 2.0);
+            }
+        });
+
+        assert_eq!(visited, ["1.0", "2.0", r#""x""#]);
+        let expected = Value::Array(vec![
+            Value::Number(Number::F64(2.0)),
+            object(vec![(
+                "a",
+                Value::Array(vec![Value::Number(Number::F64(4.0))]),
+            )]),
+            Value::String("x".into()),
+        ]);
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn shrink_to_fit_releases_capacity() {
+        let large: Value = (0..1000)
+            .map(|i| Value::Number(Number::F64(i as f64)))
+            .collect();
+        let mut value = Value::Array(vec![large]);
+        let Value::Array(outer) = &mut value else {
+            unreachable!()
+        };
+        let Value::Array(inner) = &mut outer[0] else {
+            unreachable!()
+        };
+        inner.truncate(1);
+        assert!(inner.capacity() >= 1000);
+
+        value.shrink_to_fit();
+
+        let Value::Array(outer) = &value else {
+            unreachable!()
+        };
+        let Value::Array(inner) = &outer[0] else {
+            unreachable!()
+        };
+        // shrink_to_fit 只保证不低于长度，不保证正好等于长度
+        assert!(inner.capacity() < 1000);
+        assert_eq!(inner[0], Value::Number(Number::F64(0.0)));
+    }
+
+    #[test]
+    fn ensure_array_wraps_scalar() {
+        let mut value = Value::Number(Number::F64(1.0));
+        assert_eq!(value.ensure_array().len(), 1);
+        assert_eq!(value, Value::Array(vec![Value::Number(Number::F64(1.0))]));
+    }
+
+    #[test]
+    fn ensure_array_keeps_array() {
+        let mut value = Value::Array(vec![Value::Null, Value::Null]);
+        value.ensure_array();
+        assert_eq!(value, Value::Array(vec![Value::Null, Value::Null]));
+    }
+
+    #[test]
+    fn ensure_array_wraps_null() {
+        let mut value = Value::Null;
+        value.ensure_array().push(Value::Boolean(true));
+        assert_eq!(value, Value::Array(vec![Value::Null, Value::Boolean(true)]));
+    }
+
+    #[test]
+    fn ensure_object_wraps_under_key() {
+        let mut value = Value::String("x".into());
+        value.ensure_object("item");
+        assert_eq!(value, object(vec![("item", Value::String("x".into()))]));
+
+        let mut value = object(vec![("a", Value::Null)]);
+        value.ensure_object("item");
+        assert_eq!(value, object(vec![("a", Value::Null)]));
+    }
+
+    #[test]
+    fn ensure_object_wraps_null() {
+        let mut value = Value::Null;
+        value
+            .ensure_object("item")
+            .insert("other".into(), Value::Boolean(true));
+        assert_eq!(
+            value,
+            object(vec![("item", Value::Null), ("other", Value::Boolean(true))])
+        );
+    }
+
+    #[test]
+    fn trims_nested_strings() {
+        let mut value = object(vec![(
+            " key ",
+            Value::Array(vec![Value::String("  hi  ".into()), Value::Null]),
+        )]);
+        value.trim_strings(false);
+        assert_eq!(
+            value,
+            object(vec![(
+                " key ",
+                Value::Array(vec![Value::String("hi".into()), Value::Null])
+            )])
+        );
+
+        value.trim_strings(true);
+        assert_eq!(
+            value,
+            object(vec![(
+                "key",
+                Value::Array(vec![Value::String("hi".into()), Value::Null])
+            )])
+        );
+    }
+
+    #[test]
+    fn prettifies_integral_numbers() {
+        let mut value = Value::Array(vec![
+            Value::Number(Number::F64(1.0)),
+            Value::Number(Number::F64(2.0)),
+            Value::Number(Number::F64(2.5)),
+            Value::Number(Number::F64(1e19)),
+            Value::Number(Number::F64(1e300)),
+            Value::Number(Number::F64(-0.0)),
+        ]);
+        value.prettify_numbers();
+
+        let Value::Array(numbers) = value else {
+            panic!("expected an array");
+        };
+        let debug: Vec<_> = numbers.iter().map(|number| format!("{number:?}")).collect();
+        assert_eq!(
+            debug,
+            ["1", "2", "2.5", "10000000000000000000", "1e300", "-0.0"]
+        );
+        assert!(matches!(numbers[3], Value::Number(Number::U64(_))));
+    }
+}
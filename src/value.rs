@@ -1,7 +1,12 @@
+use crate::number::Number;
+use crate::pointer::escape_token;
 use std::collections::HashMap;
+use std::fmt;
+use std::mem;
+use std::ops::Index;
 
 /// Representation of a JSON value
-#[derive(Debug, PartialEq)]
+#[derive(Clone, PartialEq)]
 pub enum Value {
     /// literal characters `null`
     Null,
@@ -9,10 +14,934 @@ pub enum Value {
     Boolean(bool),
     /// characters within double quotes "..."
     String(String),
-    /// numbers stored as a 64-bit floating point
-    Number(f64),
+    /// numbers, kept as integers when they have an exact integer value
+    Number(Number),
     /// Zero to many JSON values
     Array(Vec<Value>),
     /// String keys with JSON values
     Object(HashMap<String, Value>),
 }
+
+impl Value {
+    /// Whether the value is `Null`
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    /// Borrows the string of a `String` value
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// Reads a `Number` value as a float, possibly rounding large integers
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(number.as_f64()),
+            _ => None,
+        }
+    }
+
+    /// Reads a `Boolean` value; see `as_bool_lenient` for looser spellings
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Boolean(boolean) => Some(*boolean),
+            _ => None,
+        }
+    }
+
+    /// Borrows the elements of an `Array` value
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Borrows the entries of an `Object` value
+    pub fn as_object(&self) -> Option<&HashMap<String, Value>> {
+        match self {
+            Value::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Iterates over the elements of an `Array` value, in order
+    pub fn iter_array(&self) -> Option<impl Iterator<Item = &Value>> {
+        self.as_array().map(|array| array.iter())
+    }
+
+    /// Iterates over the entries of an `Object` value, in unspecified order
+    pub fn iter_object(&self) -> Option<impl Iterator<Item = (&String, &Value)>> {
+        self.as_object().map(|map| map.iter())
+    }
+
+    /// Moves the string out of a `String` value, or gives the value back
+    pub fn into_string(self) -> Result<String, Value> {
+        match self {
+            Value::String(string) => Ok(string),
+            other => Err(other),
+        }
+    }
+
+    /// Moves the elements out of an `Array` value, or gives the value back
+    pub fn into_array(self) -> Result<Vec<Value>, Value> {
+        match self {
+            Value::Array(array) => Ok(array),
+            other => Err(other),
+        }
+    }
+
+    /// Moves the entries out of an `Object` value, or gives the value back
+    pub fn into_object(self) -> Result<HashMap<String, Value>, Value> {
+        match self {
+            Value::Object(map) => Ok(map),
+            other => Err(other),
+        }
+    }
+
+    /// Estimates the heap memory owned by this value and all of its children,
+    /// in bytes
+    ///
+    /// String, vec and map allocations are counted by capacity; the map's own
+    /// bookkeeping overhead is not included.
+    pub fn deep_size_bytes(&self) -> usize {
+        let mut size = 0;
+        let mut stack = vec![self];
+
+        while let Some(value) = stack.pop() {
+            match value {
+                Value::String(string) => size += string.capacity(),
+                Value::Array(array) => {
+                    size += array.capacity() * mem::size_of::<Value>();
+                    stack.extend(array);
+                }
+                Value::Object(map) => {
+                    size += map.capacity() * mem::size_of::<(String, Value)>();
+                    for (key, item) in map {
+                        size += key.capacity();
+                        stack.push(item);
+                    }
+                }
+                Value::Null | Value::Boolean(_) | Value::Number(_) => {}
+            }
+        }
+        size
+    }
+
+    /// Follows a dotted path such as `"a.b.0"` and returns the value at its end
+    ///
+    /// Each segment is looked up as a key in objects and as an index in
+    /// arrays; an empty path refers to the value itself. Because `.` always
+    /// separates segments, keys containing a dot cannot be reached this way.
+    pub fn get_mut_path(&mut self, path: &str) -> Option<&mut Value> {
+        if path.is_empty() {
+            return Some(self);
+        }
+        path.split('.')
+            .try_fold(self, |value, segment| match value {
+                Value::Object(map) => map.get_mut(segment),
+                Value::Array(array) => array.get_mut(segment.parse::<usize>().ok()?),
+                _ => None,
+            })
+    }
+
+    /// Returns the member `key` of an object, first inserting `default()`
+    /// if it is missing, e.g. to build nested structures step by step
+    ///
+    /// Returns `None`, without calling `default`, for values that are not
+    /// objects.
+    pub fn get_or_insert_with(
+        &mut self,
+        key: &str,
+        default: impl FnOnce() -> Value,
+    ) -> Option<&mut Value> {
+        let Value::Object(map) = self else {
+            return None;
+        };
+        Some(map.entry(key.to_owned()).or_insert_with(default))
+    }
+
+    /// Looks up an object member ignoring ASCII case, so `"Title"` finds the
+    /// key `"title"`
+    ///
+    /// An exact match is preferred. Otherwise, if several keys differ only in
+    /// case, which one is returned is unspecified, as objects are unordered.
+    pub fn get_ci(&self, key: &str) -> Option<&Value> {
+        let Value::Object(map) = self else {
+            return None;
+        };
+        map.get(key).or_else(|| {
+            map.iter()
+                .find(|(candidate, _)| candidate.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value)
+        })
+    }
+
+    /// Iterates over every number in the tree, depth first and in array order
+    pub fn numbers(&self) -> impl Iterator<Item = f64> + '_ {
+        Nodes { stack: vec![self] }.filter_map(|value| match value {
+            Value::Number(number) => Some(number.as_f64()),
+            _ => None,
+        })
+    }
+
+    /// Adds up an array of numbers; `None` unless every element is a number
+    ///
+    /// To aggregate over a whole tree instead, sum [`Value::numbers`].
+    pub fn sum_numbers(&self) -> Option<f64> {
+        self.array_numbers().map(|numbers| numbers.sum())
+    }
+
+    /// The largest element of a non-empty array of numbers, ignoring NaN;
+    /// `None` unless every element is a number
+    pub fn max_number(&self) -> Option<f64> {
+        self.array_numbers()?.reduce(f64::max)
+    }
+
+    /// The smallest element of a non-empty array of numbers, ignoring NaN;
+    /// `None` unless every element is a number
+    pub fn min_number(&self) -> Option<f64> {
+        self.array_numbers()?.reduce(f64::min)
+    }
+
+    /// 数组中所有元素都是数字时返回它们的迭代器
+    fn array_numbers(&self) -> Option<impl Iterator<Item = f64> + '_> {
+        let array = self.as_array()?;
+        if !array.iter().all(|item| matches!(item, Value::Number(_))) {
+            return None;
+        }
+        Some(array.iter().filter_map(Value::as_f64))
+    }
+
+    /// Returns a copy for logging in which arrays longer than `max_array` keep
+    /// only their first `max_array` elements followed by a `"... N more"`
+    /// marker string, and strings longer than `max_str` characters are cut
+    /// and end in `…`
+    pub fn truncated(&self, max_array: usize, max_str: usize) -> Value {
+        let mut copy = Value::Null;
+        // 原值和它在副本中的位置；容器先放进元素都是 null 的副本，再逐个填入
+        let mut stack = vec![(self, &mut copy)];
+
+        while let Some((value, slot)) = stack.pop() {
+            match value {
+                Value::String(string) => {
+                    *slot = match string.char_indices().nth(max_str) {
+                        Some((end, _)) => Value::String(format!("{}…", &string[..end])),
+                        None => Value::String(string.clone()),
+                    }
+                }
+                Value::Array(array) => {
+                    let kept = &array[..array.len().min(max_array)];
+                    let mut items = vec![Value::Null; kept.len()];
+                    if array.len() > max_array {
+                        items.push(Value::String(format!(
+                            "... {} more",
+                            array.len() - max_array
+                        )));
+                    }
+                    *slot = Value::Array(items);
+                    let Value::Array(items) = slot else {
+                        unreachable!()
+                    };
+                    stack.extend(kept.iter().zip(items));
+                }
+                Value::Object(map) => {
+                    *slot =
+                        Value::Object(map.keys().map(|key| (key.clone(), Value::Null)).collect());
+                    let Value::Object(items) = slot else {
+                        unreachable!()
+                    };
+                    stack.extend(items.iter_mut().map(|(key, item)| (&map[key], item)));
+                }
+                Value::Null | Value::Boolean(_) | Value::Number(_) => *slot = value.clone(),
+            }
+        }
+        copy
+    }
+
+    /// Folds `f` over the value and all of its descendants, depth first and
+    /// in array order, stopping at the first `Err`
+    ///
+    /// Containers are visited before their elements. Object members are
+    /// visited in unspecified order.
+    pub fn try_fold<B, E>(
+        &self,
+        init: B,
+        f: impl FnMut(B, &Value) -> Result<B, E>,
+    ) -> Result<B, E> {
+        Nodes { stack: vec![self] }.try_fold(init, f)
+    }
+
+    /// Collects every value in the tree for which `pred` returns `true`,
+    /// together with its JSON Pointer (`""` for the root itself)
+    pub fn find_all(&self, pred: impl Fn(&Value) -> bool) -> Vec<(String, &Value)> {
+        let mut found = Vec::new();
+        let mut stack = vec![(String::new(), self)];
+
+        while let Some((path, value)) = stack.pop() {
+            if pred(value) {
+                found.push((path.clone(), value));
+            }
+            match value {
+                Value::Array(array) => {
+                    // 逆序入栈，保证按数组顺序访问
+                    for (i, item) in array.iter().enumerate().rev() {
+                        stack.push((format!("{path}/{i}"), item));
+                    }
+                }
+                Value::Object(map) => {
+                    for (key, item) in map {
+                        stack.push((format!("{path}/{}", escape_token(key)), item));
+                    }
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Maps the JSON Pointer of every leaf to a clone of it; scalars and
+    /// empty arrays or objects count as leaves
+    pub fn flatten_to_map(&self) -> HashMap<String, Value> {
+        let mut flat = HashMap::new();
+        let mut stack = vec![(String::new(), self)];
+
+        while let Some((path, value)) = stack.pop() {
+            match value {
+                Value::Array(array) if !array.is_empty() => {
+                    for (i, item) in array.iter().enumerate() {
+                        stack.push((format!("{path}/{i}"), item));
+                    }
+                }
+                Value::Object(map) if !map.is_empty() => {
+                    for (key, item) in map {
+                        stack.push((format!("{path}/{}", escape_token(key)), item));
+                    }
+                }
+                leaf => {
+                    flat.insert(path, leaf.clone());
+                }
+            }
+        }
+        flat
+    }
+
+    /// Like [`Value::flatten_to_map`], but as pairs sorted by pointer, so the
+    /// output is deterministic, e.g. for golden-file snapshots
+    pub fn to_flat_pairs_sorted(&self) -> Vec<(String, Value)> {
+        let mut pairs: Vec<_> = self.flatten_to_map().into_iter().collect();
+        pairs.sort_by(|(a, _), (b, _)| a.cmp(b));
+        pairs
+    }
+
+    /// Groups the elements of an array of objects by the text of their `key`
+    /// member, e.g. rank entries by `"stage"`; returns `None` for non-arrays
+    ///
+    /// Scalars are grouped by [`Value::to_scalar_string`] text, so `1` and
+    /// `"1"` share a group, and arrays or objects by their compact JSON.
+    /// Elements without the member are left out.
+    pub fn group_by(&self, key: &str) -> Option<HashMap<String, Vec<&Value>>> {
+        let Value::Array(array) = self else {
+            return None;
+        };
+        let mut groups: HashMap<String, Vec<&Value>> = HashMap::new();
+        for item in array {
+            let Some(member) = item.as_object().and_then(|map| map.get(key)) else {
+                continue;
+            };
+            let text = member
+                .to_scalar_string(Some("null"))
+                .unwrap_or_else(|| member.to_string());
+            groups.entry(text).or_default().push(item);
+        }
+        Some(groups)
+    }
+
+    /// Returns the entries of an object ordered by `key_fn` of their values,
+    /// e.g. to rank a leaderboard, or `None` for any other value
+    ///
+    /// Entries for which `key_fn` returns `None` come last; ties are ordered by
+    /// key so the result does not depend on the map's iteration order.
+    pub fn entries_sorted_by(
+        &self,
+        key_fn: impl Fn(&Value) -> Option<f64>,
+    ) -> Option<Vec<(&String, &Value)>> {
+        let Value::Object(map) = self else {
+            return None;
+        };
+        let mut entries: Vec<_> = map
+            .iter()
+            .map(|(key, value)| (key_fn(value), key, value))
+            .collect();
+        entries.sort_by(|(a, a_key, _), (b, b_key, _)| {
+            let by_value = match (a, b) {
+                (Some(a), Some(b)) => a.total_cmp(b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            };
+            by_value.then_with(|| a_key.cmp(b_key))
+        });
+        Some(
+            entries
+                .into_iter()
+                .map(|(_, key, value)| (key, value))
+                .collect(),
+        )
+    }
+}
+
+/// Depth-first iterator over a value and all of its descendants
+struct Nodes<'a> {
+    stack: Vec<&'a Value>,
+}
+
+impl<'a> Iterator for Nodes<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.stack.pop()?;
+        match value {
+            Value::Array(array) => self.stack.extend(array.iter().rev()),
+            Value::Object(map) => self.stack.extend(map.values()),
+            _ => {}
+        }
+        Some(value)
+    }
+}
+
+/// Prints the tree in a JSON-like layout with containers labelled by type and
+/// length, e.g. `Object(1) {"key": Array(2) [1.0, null]}`
+///
+/// `{:#?}` puts every element on its own indented line. This is meant for
+/// reading, not for producing JSON.
+impl fmt::Debug for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => f.write_str("null"),
+            Value::Boolean(boolean) => write!(f, "{boolean}"),
+            Value::Number(number) => write!(f, "{number:?}"),
+            Value::String(string) => write!(f, "{string:?}"),
+            Value::Array(array) => {
+                write!(f, "Array({}) ", array.len())?;
+                f.debug_list().entries(array).finish()
+            }
+            Value::Object(map) => {
+                write!(f, "Object({}) ", map.len())?;
+                f.debug_map().entries(map).finish()
+            }
+        }
+    }
+}
+
+/// Collects key/value pairs into an `Object`; later duplicates of a key win
+impl FromIterator<(String, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Self {
+        Value::Object(iter.into_iter().collect())
+    }
+}
+
+/// Collects values into an `Array`
+impl FromIterator<Value> for Value {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        Value::Array(iter.into_iter().collect())
+    }
+}
+
+/// 测试中用键值对列表构造对象的简写
+#[cfg(test)]
+pub(crate) fn object(entries: Vec<(&str, Value)>) -> Value {
+    entries
+        .into_iter()
+        .map(|(key, value)| (String::from(key), value))
+        .collect()
+}
+
+static NULL: Value = Value::Null;
+
+/// Looks up an object member, e.g. `value["basic_info"]["title"]`; missing
+/// keys and non-objects give `Null`, so lookups can be chained
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        match self {
+            Value::Object(map) => map.get(key).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+/// Looks up an array element, e.g. `value["order_types"][0]`; out-of-range
+/// indices and non-arrays give `Null`, so lookups can be chained
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        match self {
+            Value::Array(array) => array.get(index).unwrap_or(&NULL),
+            _ => &NULL,
+        }
+    }
+}
+
+impl PartialEq<&str> for Value {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, Value::String(string) if string == other)
+    }
+}
+
+impl PartialEq<f64> for Value {
+    fn eq(&self, other: &f64) -> bool {
+        matches!(self, Value::Number(number) if number.as_f64() == *other)
+    }
+}
+
+impl PartialEq<bool> for Value {
+    fn eq(&self, other: &bool) -> bool {
+        matches!(self, Value::Boolean(boolean) if boolean == other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Value;
+    use crate::number::Number;
+    use std::collections::HashMap;
+
+    fn debug_sample() -> Value {
+        let mut map = HashMap::new();
+        map.insert(
+            "key".into(),
+            Value::Array(vec![Value::Number(Number::F64(1.0)), Value::Null]),
+        );
+        Value::Object(map)
+    }
+
+    #[test]
+    fn debug_reads_like_json() {
+        let actual = format!("{:?}", debug_sample());
+        assert_eq!(actual, r#"Object(1) {"key": Array(2) [1.0, null]}"#);
+    }
+
+    #[test]
+    fn alternate_debug_is_indented() {
+        let actual = format!("{:#?}", debug_sample());
+        let expected = r#"Object(1) {
+    "key": Array(2) [
+        1.0,
+        null,
+    ],
+}"#;
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn collects_pairs_into_object() {
+        let value: Value = ["a", "b"]
+            .into_iter()
+            .map(|key| (String::from(key), Value::Null))
+            .collect();
+
+        let mut expected = HashMap::new();
+        expected.insert("a".into(), Value::Null);
+        expected.insert("b".into(), Value::Null);
+        assert_eq!(value, Value::Object(expected));
+    }
+
+    #[test]
+    fn collects_values_into_array() {
+        let value: Value = (1..=3)
+            .map(|i| Value::Number(Number::F64(i as f64)))
+            .collect();
+
+        let expected = vec![
+            Value::Number(Number::F64(1.0)),
+            Value::Number(Number::F64(2.0)),
+            Value::Number(Number::F64(3.0)),
+        ];
+        assert_eq!(value, Value::Array(expected));
+    }
+
+    #[test]
+    fn get_ci_ignores_ascii_case() {
+        let mut map = HashMap::new();
+        map.insert("title".into(), Value::String("瓜分奖".into()));
+        let value = Value::Object(map);
+
+        assert_eq!(value.get_ci("Title"), Some(&Value::String("瓜分奖".into())));
+        assert_eq!(value.get_ci("TITLE"), Some(&Value::String("瓜分奖".into())));
+        assert_eq!(value.get_ci("titles"), None);
+    }
+
+    #[test]
+    fn get_ci_prefers_exact_match() {
+        let mut map = HashMap::new();
+        map.insert("id".into(), Value::Number(Number::F64(1.0)));
+        map.insert("ID".into(), Value::Number(Number::F64(2.0)));
+        let value = Value::Object(map);
+
+        assert_eq!(value.get_ci("ID"), Some(&Value::Number(Number::F64(2.0))));
+        assert_eq!(Value::Null.get_ci("id"), None);
+    }
+
+    #[test]
+    fn sums_nested_numbers() {
+        let mut map = HashMap::new();
+        map.insert("a".into(), Value::Number(Number::F64(4.0)));
+        map.insert("b".into(), Value::String("5".into()));
+        let value = Value::Array(vec![
+            Value::Number(Number::F64(1.0)),
+            Value::Array(vec![Value::Number(Number::F64(2.0)), Value::Null]),
+            Value::Object(map),
+        ]);
+
+        assert_eq!(value.numbers().sum::<f64>(), 7.0);
+    }
+
+    #[test]
+    fn aggregates_numeric_array() {
+        let value = Value::Array(vec![
+            Value::Number(Number::F64(10.0)),
+            Value::Number(Number::F64(5.0)),
+            Value::Number(Number::F64(20.0)),
+        ]);
+        assert_eq!(value.sum_numbers(), Some(35.0));
+        assert_eq!(value.max_number(), Some(20.0));
+        assert_eq!(value.min_number(), Some(5.0));
+
+        let empty = Value::Array(vec![]);
+        assert_eq!(empty.sum_numbers(), Some(0.0));
+        assert_eq!(empty.max_number(), None);
+    }
+
+    #[test]
+    fn aggregates_reject_non_numeric_content() {
+        let mixed = Value::Array(vec![Value::Number(Number::I64(1)), Value::Null]);
+        assert_eq!(mixed.sum_numbers(), None);
+        assert_eq!(mixed.min_number(), None);
+        assert_eq!(Value::Number(Number::I64(1)).max_number(), None);
+    }
+
+    #[test]
+    fn iterates_array_elements() {
+        let value = crate::parse("[1, 2, 3]").unwrap();
+        let elements: Vec<_> = value
+            .iter_array()
+            .unwrap()
+            .filter_map(Value::as_f64)
+            .collect();
+        assert_eq!(elements, [1.0, 2.0, 3.0]);
+        assert!(value.iter_object().is_none());
+    }
+
+    #[test]
+    fn iterates_object_entries() {
+        let value = crate::parse(r#"{"a": 1}"#).unwrap();
+        let entries: Vec<_> = value.iter_object().unwrap().collect();
+        assert_eq!(
+            entries,
+            [(&"a".to_string(), &Value::Number(Number::I64(1)))]
+        );
+        assert!(value.iter_array().is_none());
+        assert!(Value::Null.iter_object().is_none());
+    }
+
+    #[test]
+    fn try_fold_counts_nodes() {
+        let value = Value::Array(vec![
+            Value::Array(vec![Value::Null, Value::Boolean(true)]),
+            Value::Number(Number::I64(1)),
+        ]);
+        assert_eq!(value.try_fold(0, |count, _| Ok::<_, ()>(count + 1)), Ok(5));
+    }
+
+    #[test]
+    fn try_fold_stops_at_first_error() {
+        let value = Value::Array(vec![
+            Value::Number(Number::I64(1)),
+            Value::String("bad".into()),
+            Value::Number(Number::I64(2)),
+        ]);
+        let mut visited = 0;
+        let result = value.try_fold(0, |count, node| {
+            visited += 1;
+            match node {
+                Value::String(string) => Err(format!("string after {count} nodes: {string}")),
+                _ => Ok(count + 1),
+            }
+        });
+        assert_eq!(result, Err("string after 2 nodes: bad".into()));
+        assert_eq!(visited, 3);
+    }
+
+    #[test]
+    fn numbers_in_array_order() {
+        let value = Value::Array(vec![
+            Value::Number(Number::F64(3.0)),
+            Value::Array(vec![Value::Number(Number::F64(1.0))]),
+            Value::Number(Number::F64(2.0)),
+        ]);
+
+        assert_eq!(value.numbers().collect::<Vec<_>>(), [3.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn compares_with_str() {
+        assert_eq!(Value::String("瓜分奖".into()), "瓜分奖");
+        assert_ne!(Value::String("瓜分奖".into()), "other");
+        assert_ne!(Value::Null, "null");
+    }
+
+    #[test]
+    fn compares_with_f64() {
+        assert_eq!(Value::Number(Number::F64(16.0)), 16.0);
+        assert_ne!(Value::Number(Number::F64(16.0)), 1.5);
+        assert_ne!(Value::String("16".into()), 16.0);
+    }
+
+    #[test]
+    fn compares_with_bool() {
+        assert_eq!(Value::Boolean(true), true);
+        assert_ne!(Value::Boolean(true), false);
+        assert_ne!(Value::Null, false);
+    }
+
+    #[test]
+    fn get_mut_path_mutates_nested_array_element() {
+        let mut inner = HashMap::new();
+        inner.insert(
+            "b".into(),
+            Value::Array(vec![
+                Value::Number(Number::F64(1.0)),
+                Value::Number(Number::F64(2.0)),
+            ]),
+        );
+        let mut map = HashMap::new();
+        map.insert("a".into(), Value::Object(inner));
+        let mut value = Value::Object(map);
+
+        *value.get_mut_path("a.b.1").unwrap() = Value::Boolean(true);
+
+        let Value::Object(map) = &value else {
+            panic!("root is an object");
+        };
+        let Value::Object(inner) = &map["a"] else {
+            panic!("a is an object");
+        };
+        assert_eq!(
+            inner["b"],
+            Value::Array(vec![Value::Number(Number::F64(1.0)), Value::Boolean(true)])
+        );
+    }
+
+    #[test]
+    fn get_mut_path_missing_segment() {
+        let mut value = Value::Array(vec![Value::Null]);
+        assert_eq!(value.get_mut_path("1"), None);
+        assert_eq!(value.get_mut_path("0.a"), None);
+        assert_eq!(
+            value.get_mut_path(""),
+            Some(&mut Value::Array(vec![Value::Null]))
+        );
+    }
+
+    #[test]
+    fn get_or_insert_with_builds_nested_defaults() {
+        let mut value = Value::Object(HashMap::new());
+        let config = value
+            .get_or_insert_with("config", || Value::Object(HashMap::new()))
+            .unwrap();
+        config.get_or_insert_with("retries", || Value::Number(Number::I64(3)));
+        assert_eq!(value["config"]["retries"], 3.0);
+
+        // 已有的值不会被覆盖
+        let config = value.get_or_insert_with("config", || Value::Null).unwrap();
+        assert_eq!(config["retries"], 3.0);
+    }
+
+    #[test]
+    fn get_or_insert_with_ignores_non_objects() {
+        let mut value = Value::Array(vec![]);
+        let inserted = value.get_or_insert_with("a", || unreachable!());
+        assert_eq!(inserted, None);
+    }
+
+    #[test]
+    fn deep_size_of_scalar_is_zero() {
+        assert_eq!(Value::Number(Number::F64(1.0)).deep_size_bytes(), 0);
+    }
+
+    #[test]
+    fn deep_size_grows_with_document() {
+        let small = Value::Array(vec![Value::String("a".into())]);
+        let large = Value::Array(vec![
+            Value::String("a much longer string".into()),
+            Value::Array(
+                (0..100)
+                    .map(|i| Value::Number(Number::F64(i as f64)))
+                    .collect(),
+            ),
+        ]);
+        assert!(small.deep_size_bytes() > 0);
+        assert!(large.deep_size_bytes() > small.deep_size_bytes());
+    }
+
+    #[test]
+    fn flattens_leaves_to_pointers() {
+        let mut inner = HashMap::new();
+        inner.insert(
+            "a/b".into(),
+            Value::Array(vec![Value::Null, Value::Boolean(true)]),
+        );
+        inner.insert("empty".into(), Value::Array(vec![]));
+        let mut map = HashMap::new();
+        map.insert("inner".into(), Value::Object(inner));
+        map.insert("n".into(), Value::Number(Number::F64(1.0)));
+
+        let flat = Value::Object(map).flatten_to_map();
+        assert_eq!(flat.len(), 4);
+        assert_eq!(flat["/n"], 1.0);
+        assert_eq!(flat["/inner/a~1b/0"], Value::Null);
+        assert_eq!(flat["/inner/a~1b/1"], true);
+        assert_eq!(flat["/inner/empty"], Value::Array(vec![]));
+    }
+
+    #[test]
+    fn flat_pairs_ignore_key_order() {
+        let build = |keys: &[&str]| -> Value {
+            keys.iter()
+                .map(|key| {
+                    let item = Value::Array(vec![Value::String((*key).into()), Value::Null]);
+                    (key.to_string(), item)
+                })
+                .collect()
+        };
+        let forward = build(&["a", "b", "c", "d"]);
+        let backward = build(&["d", "c", "b", "a"]);
+
+        let pairs = forward.to_flat_pairs_sorted();
+        assert_eq!(pairs, backward.to_flat_pairs_sorted());
+        let pointers: Vec<_> = pairs.iter().map(|(pointer, _)| pointer.as_str()).collect();
+        assert_eq!(
+            pointers,
+            ["/a/0", "/a/1", "/b/0", "/b/1", "/c/0", "/c/1", "/d/0", "/d/1"]
+        );
+    }
+
+    #[test]
+    fn flattens_scalar_root_to_empty_pointer() {
+        let flat = Value::String("x".into()).flatten_to_map();
+        assert_eq!(flat[""], "x");
+    }
+
+    #[test]
+    fn sorts_entries_by_value() {
+        let mut map = HashMap::new();
+        map.insert("a".into(), Value::Number(Number::F64(3.0)));
+        map.insert("b".into(), Value::Number(Number::F64(1.0)));
+        map.insert("c".into(), Value::Null);
+        map.insert("d".into(), Value::Number(Number::F64(1.0)));
+        let value = Value::Object(map);
+
+        let number = |value: &Value| match value {
+            Value::Number(number) => Some(number.as_f64()),
+            _ => None,
+        };
+        let keys: Vec<_> = value
+            .entries_sorted_by(number)
+            .unwrap()
+            .into_iter()
+            .map(|(key, _)| key.as_str())
+            .collect();
+        assert_eq!(keys, ["b", "d", "a", "c"]);
+        assert!(Value::Array(vec![]).entries_sorted_by(number).is_none());
+    }
+
+    #[test]
+    fn accessors_match_their_variant() {
+        let string = Value::String("hi".into());
+        let number = Value::Number(Number::F64(1.5));
+        let array = Value::Array(vec![Value::Null]);
+        let object = Value::Object(HashMap::new());
+
+        assert!(Value::Null.is_null());
+        assert!(!string.is_null());
+        assert_eq!(string.as_str(), Some("hi"));
+        assert_eq!(number.as_str(), None);
+        assert_eq!(number.as_f64(), Some(1.5));
+        assert_eq!(string.as_f64(), None);
+        assert_eq!(Value::Boolean(false).as_bool(), Some(false));
+        assert_eq!(Value::Null.as_bool(), None);
+        assert_eq!(array.as_array(), Some(&vec![Value::Null]));
+        assert_eq!(object.as_array(), None);
+        assert_eq!(object.as_object(), Some(&HashMap::new()));
+        assert_eq!(array.as_object(), None);
+    }
+
+    #[test]
+    fn into_string_matching() {
+        let value = Value::String("hello".into());
+        assert_eq!(value.into_string(), Ok(String::from("hello")));
+    }
+
+    #[test]
+    fn into_string_gives_value_back() {
+        assert_eq!(Value::Null.into_string(), Err(Value::Null));
+    }
+
+    #[test]
+    fn into_array_matching() {
+        let value = Value::Array(vec![Value::Boolean(true)]);
+        assert_eq!(value.into_array(), Ok(vec![Value::Boolean(true)]));
+    }
+
+    #[test]
+    fn into_array_gives_value_back() {
+        let value = Value::String("hello".into());
+        assert_eq!(value.into_array(), Err(Value::String("hello".into())));
+    }
+
+    #[test]
+    fn into_object_matching() {
+        let mut map = HashMap::new();
+        map.insert("key".into(), Value::Null);
+        let value = Value::Object(map);
+
+        let mut expected = HashMap::new();
+        expected.insert("key".into(), Value::Null);
+        assert_eq!(value.into_object(), Ok(expected));
+    }
+
+    #[test]
+    fn into_object_gives_value_back() {
+        let value = Value::Array(vec![]);
+        assert_eq!(value.into_object(), Err(Value::Array(vec![])));
+    }
+
+    #[test]
+    fn truncates_deeply_nested_array() {
+        const DEPTH: usize = 100_000;
+        let mut value = Value::Array(vec![]);
+        for _ in 1..DEPTH {
+            value = Value::Array(vec![value, Value::Null]);
+        }
+
+        // 逐层检查并拆开，避免递归比较和 drop 导致栈溢出
+        let mut copy = value.truncated(1, 10);
+        let mut depth = 0;
+        while let Value::Array(mut items) = copy {
+            depth += 1;
+            if items.is_empty() {
+                break;
+            }
+            assert_eq!(items.pop(), Some(Value::String("... 1 more".into())));
+            copy = items.pop().unwrap();
+        }
+        assert_eq!(depth, DEPTH);
+        while let Value::Array(items) = value {
+            value = items.into_iter().next().unwrap_or(Value::Null);
+        }
+    }
+}